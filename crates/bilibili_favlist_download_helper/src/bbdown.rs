@@ -1,15 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context, Result};
+use favlist_core::{CancellationToken, ProxyConfig};
 use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 
+use crate::interrupt::sleep_unless_cancelled;
+
 pub fn run_bbdown(bvid: &str, work_dir: &Path, dry_run: bool) -> Result<()> {
     if dry_run {
         println!(
@@ -39,6 +42,14 @@ pub struct ServeProcess {
 }
 
 impl ServeProcess {
+    /// 子进程是否仍在运行；Windows 下以独立窗口启动的进程无法查询，视为运行中。
+    pub fn is_running(&mut self) -> bool {
+        match self.child.as_mut() {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => self.windows_pid.is_some(),
+        }
+    }
+
     pub fn stop(&mut self) -> Result<()> {
         if let Some(child) = self.child.as_mut() {
             if let Err(err) = child.kill() {
@@ -75,6 +86,62 @@ impl Drop for ServeProcess {
     }
 }
 
+/// 按 serve 地址登记已启动的 bbdown serve；克隆共享同一份登记，
+/// Ctrl+C 强制退出时也能从信号处理线程统一关闭。
+#[derive(Clone, Default)]
+pub struct ServePool {
+    processes: Arc<Mutex<HashMap<String, ServeProcess>>>,
+}
+
+impl ServePool {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ServeProcess>> {
+        self.processes.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn insert(&self, serve_url: String, process: ServeProcess) {
+        if let Some(mut previous) = self.lock().insert(serve_url, process) {
+            let _ = previous.stop();
+        }
+    }
+
+    /// 是否登记了仍在运行的进程，已退出的进程顺带移除，返回 `None` 表示未登记。
+    pub fn is_running(&self, serve_url: &str) -> Option<bool> {
+        let mut processes = self.lock();
+        let running = processes.get_mut(serve_url)?.is_running();
+        if !running {
+            processes.remove(serve_url);
+        }
+        Some(running)
+    }
+
+    /// 关闭并移除指定地址的进程，未登记时什么也不做。
+    pub fn stop(&self, serve_url: &str) -> Result<()> {
+        let process = self.lock().remove(serve_url);
+        match process {
+            Some(mut process) => process.stop(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn serve_urls(&self) -> Vec<String> {
+        let mut urls: Vec<String> = self.lock().keys().cloned().collect();
+        urls.sort();
+        urls
+    }
+
+    /// 关闭全部进程，返回各地址的关闭结果。
+    pub fn stop_all(&self) -> Vec<(String, Result<()>)> {
+        let processes: Vec<_> = self.lock().drain().collect();
+        processes
+            .into_iter()
+            .map(|(serve_url, mut process)| {
+                let result = process.stop();
+                (serve_url, result)
+            })
+            .collect()
+    }
+}
+
 pub fn start_bbdown_serve(args: &[String], work_dir: Option<&Path>) -> Result<ServeProcess> {
     if should_launch_windows_detached() {
         start_windows_bbdown_serve(args, work_dir)
@@ -153,9 +220,14 @@ pub struct BbdownApiClient {
 }
 
 impl BbdownApiClient {
-    pub fn new(base_url: &str, timeout: Duration) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(timeout)
+    /// `proxy` 为 `None` 时直连，同时忽略环境变量中的代理设置。
+    pub fn new(base_url: &str, timeout: Duration, proxy: Option<&ProxyConfig>) -> Result<Self> {
+        let builder = Client::builder().timeout(timeout);
+        let builder = match proxy {
+            Some(proxy) => builder.proxy(proxy.to_proxy()?),
+            None => builder.no_proxy(),
+        };
+        let client = builder
             .build()
             .with_context(|| "初始化 BBDown API 客户端失败")?;
         Ok(Self {
@@ -214,11 +286,13 @@ impl BbdownApiClient {
         }
     }
 
+    /// 轮询直到目标任务全部完成；`cancel` 触发后在下一次轮询前返回错误。
     pub fn wait_until_idle<F>(
         &self,
         poll: Duration,
         timeout: Duration,
         targets: &[String],
+        cancel: &CancellationToken,
         mut on_tick: F,
     ) -> Result<()>
     where
@@ -276,7 +350,7 @@ impl BbdownApiClient {
                 break;
             }
 
-            thread::sleep(poll);
+            sleep_unless_cancelled(cancel, poll)?;
         }
         Ok(())
     }
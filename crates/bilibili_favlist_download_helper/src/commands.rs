@@ -0,0 +1,770 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use console::style;
+use favlist_core::{FavSource, ProxyConfig};
+use serde_json::json;
+
+use crate::config::{FavConfig, GlobalDefaults, DEFAULT_BBDOWN_URL, DEFAULT_POLL_INTERVAL_MS};
+use crate::interrupt::INTERRUPTED_EXIT_CODE;
+use crate::schedule::Schedule;
+use crate::watch::WatchArgs;
+use crate::{canonical_fav_url, config_label, parse_args, App, MissingOutcome, SyncSummary};
+
+/// 参数无效或找不到指定配置，与 clap 的参数错误一致。
+pub const EXIT_USAGE: u8 = 2;
+/// 部分收藏夹同步失败，或检查缺漏后仍有条目缺失（dry-run 时为存在缺漏）。
+pub const EXIT_INCOMPLETE: u8 = 3;
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 列出已保存的配置
+    List {
+        /// 以 JSON 输出，便于脚本处理
+        #[arg(long)]
+        json: bool,
+    },
+    /// 录入新收藏夹并首次抓取
+    Add(AddArgs),
+    /// 修改配置，未指定的选项保持不变，可选项传 `-` 清除
+    Edit(EditArgs),
+    /// 删除配置（保留 CSV 与已下载的文件）
+    Remove {
+        /// 配置序号（见 list，从 1 开始）或名称
+        target: String,
+    },
+    /// 检查更新：抓取收藏夹、比对变更并下载新增条目
+    Sync(SyncArgs),
+    /// 检查缺漏并通过 bbdown serve 补全
+    CheckMissing(TargetArgs),
+    /// 查看或修改全局默认，不带选项时只打印当前设置
+    Defaults(DefaultsArgs),
+    /// 守护模式：按各配置的同步计划定期检查更新与缺漏，Ctrl+C 退出（退出码 130）
+    Watch(WatchArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TargetArgs {
+    /// 配置序号（见 list，从 1 开始）或名称
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    target: Option<String>,
+
+    /// 处理全部配置
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+
+    /// 已取消收藏视频的本地文件处理方式
+    #[arg(long, value_enum, default_value_t = RemovedAction::Keep)]
+    removed: RemovedAction,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovedAction {
+    /// 保留本地文件
+    Keep,
+    /// 移动到下载目录下的 _removed/
+    Archive,
+    /// 直接删除
+    Delete,
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// 收藏夹 / 合集 / 系列 / 稍后再看 URL，也可为数字ID、ml号或 b23.tv 短链接
+    fav_url: String,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// 配置序号（见 list，从 1 开始）或名称
+    target: String,
+
+    /// 收藏夹 URL
+    #[arg(long)]
+    fav_url: Option<String>,
+
+    /// CSV 路径
+    #[arg(long)]
+    csv_path: Option<String>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+/// `add` 与 `edit` 共用的配置项；录入时未指定的项取全局默认或交互录入时的默认值。
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    /// 配置名称
+    #[arg(long)]
+    name: Option<String>,
+
+    /// BBDown 下载目录(Windows)
+    #[arg(long)]
+    api_download_dir: Option<String>,
+
+    /// 本地检测目录(WSL)，不存在时自动创建
+    #[arg(long)]
+    scan_download_dir: Option<String>,
+
+    /// CSV 编码
+    #[arg(long)]
+    encoding: Option<String>,
+
+    /// 分页大小
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    page_size: Option<u32>,
+
+    /// 请求超时（秒）
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Cookie 字符串
+    #[arg(long)]
+    cookie: Option<String>,
+
+    /// Cookie 文件（cookies.txt 或 BBDown.data），设置后不再保存明文 Cookie
+    #[arg(long)]
+    cookie_file: Option<String>,
+
+    /// 请求代理（http/https/socks5，可含账号密码）
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// B站 API 地址，可指向模拟服务器
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// BBDown serve 地址
+    #[arg(long)]
+    serve_url: Option<String>,
+
+    /// 访问 bbdown serve 时是否绕过代理
+    #[arg(long, value_name = "BOOL")]
+    bypass_proxy: Option<bool>,
+
+    /// 检查缺漏时是否自动启动 bbdown serve
+    #[arg(long, value_name = "BOOL")]
+    auto_launch: Option<bool>,
+
+    /// bbdown serve 启动附加参数（空格分隔）
+    #[arg(long, allow_hyphen_values = true)]
+    launch_args: Option<String>,
+
+    /// 任务状态轮询间隔（毫秒）
+    #[arg(long, value_parser = clap::value_parser!(u64).range(50..))]
+    poll_interval_ms: Option<u64>,
+
+    /// BBDown File Pattern
+    #[arg(long)]
+    file_pattern: Option<String>,
+
+    /// BBDown Multi File Pattern
+    #[arg(long)]
+    multi_file_pattern: Option<String>,
+
+    /// BBDown 工作目录(Windows)
+    #[arg(long)]
+    work_dir: Option<String>,
+
+    /// 下载任务并发数量(>=1)
+    #[arg(long)]
+    max_concurrency: Option<String>,
+
+    /// 检查更新时是否使用增量同步
+    #[arg(long, value_name = "BOOL")]
+    incremental: Option<bool>,
+
+    /// 守护模式同步计划：间隔（如 6h）或 cron 表达式（如 "0 3 * * *"）
+    #[arg(long)]
+    schedule: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct DefaultsArgs {
+    /// 默认 BBDown 下载目录(Windows)
+    #[arg(long)]
+    api_download_dir: Option<String>,
+
+    /// 默认本地检测目录(WSL)
+    #[arg(long)]
+    scan_download_dir: Option<String>,
+
+    /// 默认 BBDown serve 地址
+    #[arg(long)]
+    serve_url: Option<String>,
+
+    /// 默认 File Pattern
+    #[arg(long)]
+    file_pattern: Option<String>,
+
+    /// 默认 Multi File Pattern
+    #[arg(long)]
+    multi_file_pattern: Option<String>,
+
+    /// 默认 BBDown 工作目录
+    #[arg(long)]
+    work_dir: Option<String>,
+
+    /// 默认下载任务并发数量(>=1)
+    #[arg(long)]
+    max_concurrency: Option<String>,
+
+    /// 请求速率上限（次/秒）
+    #[arg(long)]
+    requests_per_second: Option<String>,
+
+    /// 同步全部时同时抓取的收藏夹数量(>=1)
+    #[arg(long)]
+    sync_concurrency: Option<String>,
+
+    /// 默认请求代理
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// 访问 bbdown serve 时是否绕过代理
+    #[arg(long, value_name = "BOOL")]
+    bypass_proxy: Option<bool>,
+
+    /// 默认B站 API 地址
+    #[arg(long)]
+    base_url: Option<String>,
+}
+
+impl App {
+    /// 执行子命令，返回进程退出码。
+    pub(crate) fn run_command(&mut self, command: Command) -> Result<ExitCode> {
+        match command {
+            Command::List { json } => self.list_configs(json),
+            Command::Add(args) => self.add_config(args),
+            Command::Edit(args) => self.edit_config_with(args),
+            Command::Remove { target } => {
+                let index = match find_config(self.store.configs(), &target) {
+                    Ok(index) => index,
+                    Err(err) => return Ok(usage_error(err)),
+                };
+                let removed = self.store.remove(index)?;
+                println!(
+                    "已删除配置 {}（CSV 与已下载的文件保持不变）",
+                    config_label(&removed, index)
+                );
+                Ok(ExitCode::SUCCESS)
+            }
+            Command::Sync(args) => {
+                let indices = match self.target_indices(&args.target) {
+                    Ok(indices) => indices,
+                    Err(err) => return Ok(usage_error(err)),
+                };
+                if indices.is_empty() {
+                    println!("当前没有任何已保存的配置。");
+                    return Ok(ExitCode::SUCCESS);
+                }
+                self.removed_action = Some(args.removed);
+                let summary = self.sync_configs(indices)?;
+                Ok(sync_exit_code(&summary))
+            }
+            Command::CheckMissing(args) => {
+                let indices = match self.target_indices(&args) {
+                    Ok(indices) => indices,
+                    Err(err) => return Ok(usage_error(err)),
+                };
+                let mut incomplete = false;
+                for index in indices {
+                    let label = config_label(&self.store.configs()[index], index);
+                    println!("{}", style(format!("== {label} ==")).cyan());
+                    match self.fill_missing(index)? {
+                        MissingOutcome::Complete => {}
+                        MissingOutcome::Interrupted => {
+                            return Ok(ExitCode::from(INTERRUPTED_EXIT_CODE))
+                        }
+                        MissingOutcome::Remaining(_) | MissingOutcome::Failed => incomplete = true,
+                    }
+                }
+                Ok(if incomplete {
+                    ExitCode::from(EXIT_INCOMPLETE)
+                } else {
+                    ExitCode::SUCCESS
+                })
+            }
+            Command::Defaults(args) => self.update_defaults(args),
+            Command::Watch(args) => self.watch(args),
+        }
+    }
+
+    fn list_configs(&self, as_json: bool) -> Result<ExitCode> {
+        let configs = self.store.configs();
+        if as_json {
+            let items: Vec<_> = configs
+                .iter()
+                .enumerate()
+                .map(|(index, config)| {
+                    json!({
+                        "index": index + 1,
+                        "name": config_label(config, index),
+                        "fav_url": config.fav_url,
+                        "csv_path": config.csv_path,
+                        "scan_download_dir": config.scan_download_dir(),
+                        "last_synced_at": config.last_synced_at,
+                        "schedule": config.schedule,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&items)?);
+            return Ok(ExitCode::SUCCESS);
+        }
+        if configs.is_empty() {
+            println!("当前没有任何已保存的配置。");
+        }
+        for (index, config) in configs.iter().enumerate() {
+            println!(
+                "{}. {} -> {}",
+                index + 1,
+                style(config_label(config, index)).cyan(),
+                config.fav_url
+            );
+            println!(
+                "   CSV: {}  上次同步: {}",
+                config.csv_path,
+                config.last_synced_at.as_deref().unwrap_or("从未")
+            );
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn add_config(&mut self, args: AddArgs) -> Result<ExitCode> {
+        let defaults = self.defaults.data().clone();
+        let current_dir = env::current_dir()
+            .unwrap_or_else(|_| ".".into())
+            .display()
+            .to_string();
+        let mut config = FavConfig {
+            fav_url: String::new(),
+            legacy_download_dir: None,
+            api_download_dir: defaults
+                .api_download_dir
+                .clone()
+                .unwrap_or_else(|| current_dir.clone()),
+            scan_download_dir: Some(defaults.scan_download_dir.clone().unwrap_or(current_dir)),
+            bbdown_work_dir: None,
+            bbdown_max_concurrency: None,
+            csv_path: String::new(),
+            encoding: "utf-8".to_string(),
+            page_size: 40,
+            cookie: None,
+            cookie_file: None,
+            timeout_secs: 10,
+            last_synced_at: None,
+            name: None,
+            bbdown_serve_url: defaults
+                .bbdown_serve_url
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BBDOWN_URL.to_string()),
+            bbdown_auto_launch: true,
+            bbdown_launch_args: Vec::new(),
+            bbdown_poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            file_pattern: defaults.file_pattern.clone(),
+            multi_file_pattern: defaults.multi_file_pattern.clone(),
+            incremental: false,
+            last_fav_time: None,
+            space_url: None,
+            proxy: None,
+            bbdown_bypass_proxy: None,
+            base_url: None,
+            schedule: None,
+        };
+        if let Err(err) = apply_config_args(&mut config, args.config) {
+            return Ok(usage_error(err));
+        }
+        let (fav_url, source) =
+            match canonical_fav_url(&args.fav_url, config.proxy_config(&defaults)) {
+                Ok(resolved) => resolved,
+                Err(err) => return Ok(usage_error(err)),
+            };
+        config.fav_url = fav_url;
+        let scan_dir = config.scan_download_dir_path();
+        fs::create_dir_all(&scan_dir)
+            .with_context(|| format!("创建目录失败: {}", scan_dir.display()))?;
+        if config.cookie.is_none() && config.cookie_file.is_none() && source.requires_cookie() {
+            println!("{}", style("未指定 Cookie，抓取该列表将会失败。").yellow());
+        }
+
+        if matches!(source, FavSource::UserFolders { .. }) {
+            config.space_url = Some(config.fav_url.clone());
+            let added = self.register_user_folders(&config)?;
+            println!("已登记 {added} 个收藏夹。");
+            return Ok(ExitCode::SUCCESS);
+        }
+        Ok(if self.export_new_config(config)? {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(INTERRUPTED_EXIT_CODE)
+        })
+    }
+
+    fn edit_config_with(&mut self, args: EditArgs) -> Result<ExitCode> {
+        let index = match find_config(self.store.configs(), &args.target) {
+            Ok(index) => index,
+            Err(err) => return Ok(usage_error(err)),
+        };
+        let mut config = self.store.configs()[index].clone();
+        if let Err(err) = apply_config_args(&mut config, args.config) {
+            return Ok(usage_error(err));
+        }
+        if let Some(csv_path) = args.csv_path {
+            config.csv_path = csv_path;
+        }
+        if let Some(fav_url) = args.fav_url {
+            let proxy = config.proxy_config(self.defaults.data());
+            config.fav_url = match canonical_fav_url(&fav_url, proxy) {
+                Ok((fav_url, _)) => fav_url,
+                Err(err) => return Ok(usage_error(err)),
+            };
+        }
+        self.store.update(index, config)?;
+        println!("{}", style("配置已更新。").green());
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn update_defaults(&mut self, args: DefaultsArgs) -> Result<ExitCode> {
+        let before = serde_json::to_value(self.defaults.data())?;
+        let mut data = self.defaults.data().clone();
+        if let Err(err) = apply_defaults_args(&mut data, args) {
+            return Ok(usage_error(err));
+        }
+
+        if serde_json::to_value(&data)? != before {
+            *self.defaults.data_mut() = data;
+            self.defaults.save()?;
+            self.rate_limiter = self.defaults.data().rate_limiter();
+            println!("{}", style("全局默认已更新。").green());
+        }
+        println!("{}", serde_json::to_string_pretty(self.defaults.data())?);
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn target_indices(&self, args: &TargetArgs) -> Result<Vec<usize>> {
+        match args.target.as_deref() {
+            Some(target) => Ok(vec![find_config(self.store.configs(), target)?]),
+            None => Ok((0..self.store.configs().len()).collect()),
+        }
+    }
+}
+
+/// 按序号（从 1 开始）或名称查找配置，同名配置需改用序号。
+pub(crate) fn find_config(configs: &[FavConfig], target: &str) -> Result<usize> {
+    let target = target.trim();
+    if let Ok(number) = target.parse::<usize>() {
+        if (1..=configs.len()).contains(&number) {
+            return Ok(number - 1);
+        }
+        bail!("配置序号 {number} 超出范围（共 {} 个配置）", configs.len());
+    }
+    let matches: Vec<usize> = configs
+        .iter()
+        .enumerate()
+        .filter(|(index, config)| config_label(config, *index) == target)
+        .map(|(index, _)| index)
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => bail!("未找到名为「{target}」的配置，可用 list 查看"),
+        _ => bail!("有 {} 个配置名为「{target}」，请改用序号", matches.len()),
+    }
+}
+
+fn apply_config_args(config: &mut FavConfig, args: ConfigArgs) -> Result<()> {
+    config.name = update_optional(args.name, config.name.take())?;
+    if let Some(dir) = args.api_download_dir {
+        config.api_download_dir = dir;
+    }
+    if let Some(dir) = args.scan_download_dir {
+        fs::create_dir_all(Path::new(&dir)).with_context(|| format!("创建目录失败: {dir}"))?;
+        config.scan_download_dir = Some(dir);
+    }
+    if let Some(encoding) = args.encoding {
+        config.encoding = encoding;
+    }
+    if let Some(page_size) = args.page_size {
+        config.page_size = page_size;
+    }
+    if let Some(timeout) = args.timeout {
+        config.timeout_secs = timeout;
+    }
+    config.cookie_file = update_optional(args.cookie_file, config.cookie_file.take())?;
+    config.cookie = if config.cookie_file.is_some() {
+        // 改用 Cookie 文件后不再保留明文 Cookie。
+        None
+    } else {
+        update_optional(args.cookie, config.cookie.take())?
+    };
+    config.proxy = checked_proxy(update_optional(args.proxy, config.proxy.take())?)?;
+    config.base_url = update_optional(args.base_url, config.base_url.take())?
+        .map(|url: String| url.trim_end_matches('/').to_string());
+    if let Some(serve_url) = args.serve_url {
+        config.bbdown_serve_url = serve_url;
+    }
+    if let Some(bypass) = args.bypass_proxy {
+        config.bbdown_bypass_proxy = Some(bypass);
+    }
+    if let Some(auto_launch) = args.auto_launch {
+        config.bbdown_auto_launch = auto_launch;
+    }
+    match args.launch_args.as_deref().map(str::trim) {
+        Some("-") => config.bbdown_launch_args.clear(),
+        Some(input) => config.bbdown_launch_args = parse_args(input),
+        None => {}
+    }
+    if let Some(interval) = args.poll_interval_ms {
+        config.bbdown_poll_interval_ms = interval;
+    }
+    config.file_pattern = update_optional(args.file_pattern, config.file_pattern.take())?;
+    config.multi_file_pattern =
+        update_optional(args.multi_file_pattern, config.multi_file_pattern.take())?;
+    config.bbdown_work_dir = update_optional(args.work_dir, config.bbdown_work_dir.take())?;
+    config.bbdown_max_concurrency = positive(
+        update_optional(args.max_concurrency, config.bbdown_max_concurrency)?,
+        "max-concurrency",
+    )?;
+    if let Some(incremental) = args.incremental {
+        config.incremental = incremental;
+    }
+    config.schedule = update_optional(args.schedule, config.schedule.take())?;
+    if let Some(schedule) = config.schedule.as_deref() {
+        schedule.parse::<Schedule>()?;
+    }
+    Ok(())
+}
+
+fn apply_defaults_args(data: &mut GlobalDefaults, args: DefaultsArgs) -> Result<()> {
+    data.api_download_dir = update_optional(args.api_download_dir, data.api_download_dir.take())?;
+    data.scan_download_dir =
+        update_optional(args.scan_download_dir, data.scan_download_dir.take())?;
+    data.bbdown_serve_url = update_optional(args.serve_url, data.bbdown_serve_url.take())?;
+    data.file_pattern = update_optional(args.file_pattern, data.file_pattern.take())?;
+    data.multi_file_pattern =
+        update_optional(args.multi_file_pattern, data.multi_file_pattern.take())?;
+    data.bbdown_work_dir = update_optional(args.work_dir, data.bbdown_work_dir.take())?;
+    data.bbdown_max_concurrency = positive(
+        update_optional(args.max_concurrency, data.bbdown_max_concurrency)?,
+        "max-concurrency",
+    )?;
+    data.requests_per_second = positive(
+        update_optional(args.requests_per_second, data.requests_per_second)?,
+        "requests-per-second",
+    )?;
+    data.sync_concurrency = positive(
+        update_optional(args.sync_concurrency, data.sync_concurrency)?,
+        "sync-concurrency",
+    )?;
+    data.proxy = checked_proxy(update_optional(args.proxy, data.proxy.take())?)?;
+    if let Some(bypass) = args.bypass_proxy {
+        data.bbdown_bypass_proxy = Some(bypass);
+    }
+    data.base_url = update_optional(args.base_url, data.base_url.take())?
+        .map(|url: String| url.trim_end_matches('/').to_string());
+    Ok(())
+}
+
+/// 未指定时保留原值，`-` 清除，其余按目标类型解析。
+fn update_optional<T>(input: Option<String>, current: Option<T>) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match input.as_deref().map(str::trim) {
+        None => Ok(current),
+        Some("-") => Ok(None),
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|err| anyhow!("无效的值 `{value}`: {err}")),
+    }
+}
+
+fn positive<T: PartialOrd + Default + Display>(value: Option<T>, name: &str) -> Result<Option<T>> {
+    match value {
+        Some(value) if value <= T::default() => bail!("--{name} 必须大于 0，收到 {value}"),
+        value => Ok(value),
+    }
+}
+
+fn checked_proxy(proxy: Option<String>) -> Result<Option<String>> {
+    if let Some(url) = proxy.as_deref() {
+        ProxyConfig::new(url).to_proxy()?;
+    }
+    Ok(proxy)
+}
+
+pub(crate) fn usage_error(err: anyhow::Error) -> ExitCode {
+    eprintln!("{}", style(format!("{err:#}")).red());
+    ExitCode::from(EXIT_USAGE)
+}
+
+fn sync_exit_code(summary: &SyncSummary) -> ExitCode {
+    if summary.interrupted {
+        ExitCode::from(INTERRUPTED_EXIT_CODE)
+    } else if summary.failed > 0 {
+        ExitCode::from(EXIT_INCOMPLETE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct ConfigCli {
+        #[command(flatten)]
+        config: ConfigArgs,
+    }
+
+    fn config_args(args: &[&str]) -> ConfigArgs {
+        ConfigCli::try_parse_from(std::iter::once("test").chain(args.iter().copied()))
+            .expect("参数应能解析")
+            .config
+    }
+
+    fn sample_config(name: Option<&str>) -> FavConfig {
+        serde_json::from_value(json!({
+            "fav_url": "https://space.bilibili.com/1/favlist?fid=1001",
+            "csv_path": "favlist.csv",
+            "encoding": "utf-8",
+            "page_size": 20,
+            "cookie": "SESSDATA=inline",
+            "timeout_secs": 10,
+            "last_synced_at": null,
+            "name": name,
+        }))
+        .expect("示例配置应能解析")
+    }
+
+    #[test]
+    fn find_config_by_index_and_name() {
+        let configs = vec![
+            sample_config(Some("稍后看")),
+            sample_config(None),
+            sample_config(Some("音乐")),
+        ];
+        assert_eq!(find_config(&configs, "1").unwrap(), 0);
+        assert_eq!(find_config(&configs, " 3 ").unwrap(), 2);
+        assert_eq!(find_config(&configs, "音乐").unwrap(), 2);
+        // 未命名的配置按默认标签匹配。
+        assert_eq!(find_config(&configs, "收藏夹 2").unwrap(), 1);
+
+        let err = find_config(&configs, "0").unwrap_err().to_string();
+        assert!(err.contains("超出范围"), "{err}");
+        let err = find_config(&configs, "4").unwrap_err().to_string();
+        assert!(err.contains("超出范围"), "{err}");
+        let err = find_config(&configs, "不存在").unwrap_err().to_string();
+        assert!(err.contains("未找到"), "{err}");
+    }
+
+    #[test]
+    fn find_config_rejects_duplicate_names() {
+        let configs = vec![sample_config(Some("同名")), sample_config(Some("同名"))];
+        let err = find_config(&configs, "同名").unwrap_err().to_string();
+        assert!(err.contains("请改用序号"), "{err}");
+    }
+
+    #[test]
+    fn find_config_treats_numbers_as_index() {
+        // 数字形式的名称总是按序号解析，只能改用序号指定。
+        let configs = vec![sample_config(Some("2024")), sample_config(Some("1"))];
+        assert_eq!(find_config(&configs, "1").unwrap(), 0);
+        assert!(find_config(&configs, "2024").is_err());
+    }
+
+    #[test]
+    fn update_optional_keeps_clears_and_parses() {
+        assert_eq!(update_optional::<u32>(None, Some(3)).unwrap(), Some(3));
+        assert_eq!(
+            update_optional::<u32>(Some(" - ".into()), Some(3)).unwrap(),
+            None
+        );
+        assert_eq!(
+            update_optional::<u32>(Some("7".into()), None).unwrap(),
+            Some(7)
+        );
+        let err = update_optional::<u32>(Some("abc".into()), Some(3)).unwrap_err();
+        assert!(err.to_string().contains("abc"), "{err}");
+    }
+
+    #[test]
+    fn positive_rejects_zero() {
+        assert_eq!(positive(Some(2u32), "x").unwrap(), Some(2));
+        assert_eq!(positive::<u32>(None, "x").unwrap(), None);
+        let err = positive(Some(0u32), "sync-concurrency").unwrap_err();
+        assert!(err.to_string().contains("--sync-concurrency"), "{err}");
+        assert!(positive(Some(-1.5f64), "requests-per-second").is_err());
+    }
+
+    #[test]
+    fn cookie_file_drops_inline_cookie() {
+        let mut config = sample_config(None);
+        apply_config_args(&mut config, config_args(&["--cookie-file", "cookies.txt"])).unwrap();
+        assert_eq!(config.cookie_file.as_deref(), Some("cookies.txt"));
+        assert_eq!(config.cookie, None);
+
+        // 清除 Cookie 文件后才能重新设置明文 Cookie。
+        apply_config_args(
+            &mut config,
+            config_args(&["--cookie-file", "-", "--cookie", "SESSDATA=new"]),
+        )
+        .unwrap();
+        assert_eq!(config.cookie_file, None);
+        assert_eq!(config.cookie.as_deref(), Some("SESSDATA=new"));
+    }
+
+    #[test]
+    fn config_args_leave_unspecified_fields() {
+        let mut config = sample_config(Some("原名"));
+        apply_config_args(&mut config, config_args(&["--page-size", "50"])).unwrap();
+        assert_eq!(config.page_size, 50);
+        assert_eq!(config.name.as_deref(), Some("原名"));
+        assert_eq!(config.cookie.as_deref(), Some("SESSDATA=inline"));
+
+        let err = apply_config_args(&mut config, config_args(&["--max-concurrency", "0"]));
+        assert!(err.is_err());
+        let err = apply_config_args(&mut config, config_args(&["--schedule", "5s"]));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn sync_exit_codes() {
+        let summary = |failed, interrupted| SyncSummary {
+            succeeded: 1,
+            failed,
+            interrupted,
+        };
+        assert_eq!(sync_exit_code(&summary(0, false)), ExitCode::SUCCESS);
+        assert_eq!(
+            sync_exit_code(&summary(2, false)),
+            ExitCode::from(EXIT_INCOMPLETE)
+        );
+        // 中断优先于失败。
+        assert_eq!(
+            sync_exit_code(&summary(2, true)),
+            ExitCode::from(INTERRUPTED_EXIT_CODE)
+        );
+        assert_eq!(usage_error(anyhow!("无效参数")), ExitCode::from(EXIT_USAGE));
+    }
+}
@@ -4,13 +4,18 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use dirs_next::config_dir;
+use favlist_core::{IncrementalOptions, ProxyConfig, RateLimiter, DEFAULT_SYNC_CONCURRENCY};
 use serde::{Deserialize, Serialize};
 
 const APP_DIR: &str = "bilibili_favlist_helper";
 const CONFIG_NAME: &str = "config.json";
 const DEFAULTS_NAME: &str = "defaults.json";
+const LIBRARY_NAME: &str = "library.db";
+const CREDENTIALS_NAME: &str = "credentials.json";
+const WATCH_STATUS_NAME: &str = "watch-status.json";
 pub const DEFAULT_BBDOWN_URL: &str = "http://localhost:23333";
 pub const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+pub const DEFAULT_STOP_AFTER_KNOWN: usize = 20;
 
 fn default_bbdown_url() -> String {
     DEFAULT_BBDOWN_URL.to_string()
@@ -41,6 +46,9 @@ pub struct FavConfig {
     pub encoding: String,
     pub page_size: u32,
     pub cookie: Option<String>,
+    /// Netscape cookies.txt 或 BBDown.data 路径，优先于明文 `cookie`
+    #[serde(default)]
+    pub cookie_file: Option<String>,
     pub timeout_secs: u64,
     pub last_synced_at: Option<String>,
     pub name: Option<String>,
@@ -56,6 +64,25 @@ pub struct FavConfig {
     pub file_pattern: Option<String>,
     #[serde(default)]
     pub multi_file_pattern: Option<String>,
+    #[serde(default)]
+    pub incremental: bool,
+    #[serde(default)]
+    pub last_fav_time: Option<i64>,
+    /// 由用户空间链接批量登记时记录来源，检查更新时据此发现新建的收藏夹
+    #[serde(default)]
+    pub space_url: Option<String>,
+    /// 请求B站使用的代理（http/https/socks5，可含账号密码），未设置时沿用全局默认
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// 访问 bbdown serve 时是否绕过代理，未设置时沿用全局默认（默认绕过）
+    #[serde(default)]
+    pub bbdown_bypass_proxy: Option<bool>,
+    /// B站 API 地址，未设置时沿用全局默认；用于对接模拟服务器
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 守护模式下的同步计划：时长间隔（如 `6h`）或 5 段 cron 表达式（如 `0 3 * * *`）
+    #[serde(default)]
+    pub schedule: Option<String>,
 }
 
 impl FavConfig {
@@ -67,6 +94,19 @@ impl FavConfig {
         PathBuf::from(&self.csv_path)
     }
 
+    pub fn cookie_file_path(&self) -> Option<PathBuf> {
+        self.cookie_file.as_deref().map(PathBuf::from)
+    }
+
+    /// 配置了 Cookie 文件时不再使用明文 Cookie。
+    pub fn inline_cookie(&self) -> Option<String> {
+        if self.cookie_file.is_some() {
+            None
+        } else {
+            self.cookie.clone()
+        }
+    }
+
     pub fn apply_defaults(&mut self) {
         if self.bbdown_serve_url.is_empty() {
             self.bbdown_serve_url = default_bbdown_url();
@@ -112,6 +152,13 @@ impl FavConfig {
             .map(|pattern| join_download_path(&self.api_download_dir, pattern))
     }
 
+    pub fn incremental_options(&self) -> Option<IncrementalOptions> {
+        self.incremental.then_some(IncrementalOptions {
+            stop_after_known: DEFAULT_STOP_AFTER_KNOWN,
+            since_fav_time: self.last_fav_time,
+        })
+    }
+
     pub fn poll_interval(&self) -> Duration {
         Duration::from_millis(self.bbdown_poll_interval_ms.max(50))
     }
@@ -129,6 +176,30 @@ impl FavConfig {
             .max(1)
     }
 
+    pub fn proxy_config(&self, defaults: &GlobalDefaults) -> Option<ProxyConfig> {
+        self.proxy
+            .as_deref()
+            .or(defaults.proxy.as_deref())
+            .map(ProxyConfig::new)
+    }
+
+    pub fn api_base_url(&self, defaults: &GlobalDefaults) -> Option<String> {
+        self.base_url.clone().or_else(|| defaults.base_url.clone())
+    }
+
+    /// bbdown serve 通常在本机，默认不经代理。
+    pub fn bbdown_proxy_config(&self, defaults: &GlobalDefaults) -> Option<ProxyConfig> {
+        let bypass = self
+            .bbdown_bypass_proxy
+            .or(defaults.bbdown_bypass_proxy)
+            .unwrap_or(true);
+        if bypass {
+            None
+        } else {
+            self.proxy_config(defaults)
+        }
+    }
+
     pub fn resolve_work_dir(&self, defaults: &GlobalDefaults) -> Option<PathBuf> {
         self.bbdown_work_dir
             .as_ref()
@@ -186,12 +257,36 @@ impl ConfigStore {
         self.save()
     }
 
+    pub fn remove(&mut self, index: usize) -> Result<FavConfig> {
+        if index >= self.configs.len() {
+            return Err(anyhow!("配置索引超出范围"));
+        }
+        let removed = self.configs.remove(index);
+        self.save()?;
+        Ok(removed)
+    }
+
     pub fn config_dir(&self) -> PathBuf {
         self.path
             .parent()
             .map(Path::to_path_buf)
             .unwrap_or_else(|| PathBuf::from("."))
     }
+
+    /// 所有收藏夹共用的本地视频库，与配置文件放在同一目录。
+    pub fn library_path(&self) -> PathBuf {
+        self.config_dir().join(LIBRARY_NAME)
+    }
+
+    /// 扫码登录保存的凭据，可作为配置的 Cookie 文件。
+    pub fn credentials_path(&self) -> PathBuf {
+        self.config_dir().join(CREDENTIALS_NAME)
+    }
+
+    /// 守护模式默认写出的状态文件。
+    pub fn watch_status_path(&self) -> PathBuf {
+        self.config_dir().join(WATCH_STATUS_NAME)
+    }
 }
 
 fn default_config_path() -> PathBuf {
@@ -227,6 +322,36 @@ pub struct GlobalDefaults {
     pub bbdown_max_concurrency: Option<u32>,
     pub file_pattern: Option<String>,
     pub multi_file_pattern: Option<String>,
+    /// 所有收藏夹共享的请求速率上限（次/秒）
+    pub requests_per_second: Option<f64>,
+    #[serde(default)]
+    pub proxy: Option<String>,
+    #[serde(default)]
+    pub bbdown_bypass_proxy: Option<bool>,
+    /// B站 API 地址，留空使用官方接口
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// “同步全部收藏夹”时同时抓取的收藏夹数量
+    #[serde(default)]
+    pub sync_concurrency: Option<usize>,
+}
+
+impl GlobalDefaults {
+    pub fn rate_limiter(&self) -> Option<RateLimiter> {
+        self.requests_per_second
+            .filter(|value| *value > 0.0)
+            .map(RateLimiter::per_second)
+    }
+
+    pub fn proxy_config(&self) -> Option<ProxyConfig> {
+        self.proxy.as_deref().map(ProxyConfig::new)
+    }
+
+    pub fn sync_concurrency(&self) -> usize {
+        self.sync_concurrency
+            .unwrap_or(DEFAULT_SYNC_CONCURRENCY)
+            .max(1)
+    }
 }
 
 pub struct GlobalDefaultsStore {
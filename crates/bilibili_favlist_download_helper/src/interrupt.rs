@@ -0,0 +1,194 @@
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use crossterm::terminal;
+use favlist_core::CancellationToken;
+
+/// 被 Ctrl+C 中断时的退出码（128 + SIGINT）。
+pub const INTERRUPTED_EXIT_CODE: u8 = 130;
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+type CleanupHook = Box<dyn Fn() + Send>;
+
+/// Ctrl+C 处理：有操作进行时取消其令牌，让导出写完已抓取的条目、bbdown serve 正常关闭；
+/// 空闲时或再次按下时执行清理钩子、恢复终端后直接退出。
+#[derive(Clone)]
+pub struct Interrupt {
+    current: Arc<Mutex<Option<CancellationToken>>>,
+    cleanup: Arc<Mutex<Vec<CleanupHook>>>,
+}
+
+impl Interrupt {
+    pub fn install() -> Result<Self> {
+        let interrupt = Self::new();
+        let handler = interrupt.clone();
+        ctrlc::set_handler(move || {
+            if !handler.cancel_current() {
+                handler.run_cleanup();
+                terminal::disable_raw_mode().ok();
+                process::exit(i32::from(INTERRUPTED_EXIT_CODE));
+            }
+        })
+        .context("注册 Ctrl+C 处理失败")?;
+        Ok(interrupt)
+    }
+
+    /// 不注册信号处理的实例，`install` 在其上挂接 Ctrl+C。
+    fn new() -> Self {
+        Self {
+            current: Arc::new(Mutex::new(None)),
+            cleanup: Arc::default(),
+        }
+    }
+
+    /// 登记强制退出前执行的清理，例如关闭仍在运行的子进程。
+    pub fn on_force_exit(&self, hook: impl Fn() + Send + 'static) {
+        self.cleanup
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(Box::new(hook));
+    }
+
+    /// 取消进行中的操作；没有操作或已取消过时返回 `false`，此时应强制退出。
+    fn cancel_current(&self) -> bool {
+        let guard = self.current.lock().unwrap_or_else(|err| err.into_inner());
+        match guard.as_ref() {
+            Some(token) if !token.is_cancelled() => {
+                eprintln!("\n正在中断当前操作，再按一次 Ctrl+C 强制退出...");
+                token.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn run_cleanup(&self) {
+        let hooks = self.cleanup.lock().unwrap_or_else(|err| err.into_inner());
+        for hook in hooks.iter() {
+            hook();
+        }
+    }
+
+    /// 开始一个可中断的操作，返回的作用域结束时恢复外层作用域（没有时恢复默认处理）。
+    pub fn begin(&self) -> InterruptScope {
+        let token = CancellationToken::new();
+        let previous = self
+            .current
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .replace(token.clone());
+        InterruptScope {
+            current: Arc::clone(&self.current),
+            token,
+            previous,
+        }
+    }
+}
+
+pub struct InterruptScope {
+    current: Arc<Mutex<Option<CancellationToken>>>,
+    token: CancellationToken,
+    previous: Option<CancellationToken>,
+}
+
+impl InterruptScope {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Drop for InterruptScope {
+    fn drop(&mut self) {
+        *self.current.lock().unwrap_or_else(|err| err.into_inner()) = self.previous.take();
+    }
+}
+
+/// 可被令牌打断的 `thread::sleep`，被打断时返回错误。
+pub fn sleep_unless_cancelled(cancel: &CancellationToken, duration: Duration) -> Result<()> {
+    let deadline = Instant::now() + duration;
+    loop {
+        if cancel.is_cancelled() {
+            bail!("操作已中断");
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(());
+        }
+        thread::sleep(CANCEL_CHECK_INTERVAL.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn current(interrupt: &Interrupt) -> Option<CancellationToken> {
+        interrupt.current.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn nested_scopes_restore_outer_token() {
+        let interrupt = Interrupt::new();
+        let outer = interrupt.begin();
+        {
+            let inner = interrupt.begin();
+            assert!(interrupt.cancel_current());
+            assert!(inner.is_cancelled());
+            assert!(!outer.is_cancelled());
+        }
+        // 内层结束后 Ctrl+C 重新作用于外层操作。
+        assert!(interrupt.cancel_current());
+        assert!(outer.is_cancelled());
+        drop(outer);
+        assert!(current(&interrupt).is_none());
+    }
+
+    #[test]
+    fn second_interrupt_forces_exit_and_runs_cleanup() {
+        let interrupt = Interrupt::new();
+        // 空闲时直接强制退出。
+        assert!(!interrupt.cancel_current());
+
+        let scope = interrupt.begin();
+        assert!(interrupt.cancel_current());
+        assert!(scope.is_cancelled());
+        assert!(!interrupt.cancel_current());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        interrupt.on_force_exit(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        // 信号处理持有的是克隆，清理钩子需在克隆间共享。
+        interrupt.clone().run_cleanup();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn sleep_returns_early_when_cancelled() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            canceller.cancel();
+        });
+        let started = Instant::now();
+        assert!(sleep_unless_cancelled(&token, Duration::from_secs(30)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        handle.join().unwrap();
+
+        // 已取消的令牌立即返回，未取消时睡满后正常返回。
+        assert!(sleep_unless_cancelled(&token, Duration::from_secs(30)).is_err());
+        let fresh = CancellationToken::new();
+        assert!(sleep_unless_cancelled(&fresh, Duration::from_millis(20)).is_ok());
+    }
+}
@@ -1,59 +1,121 @@
 mod bbdown;
+mod commands;
 mod config;
+mod interrupt;
 mod menu;
 mod prompts;
+mod schedule;
+mod watch;
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::thread;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 use console::style;
 use crossterm::terminal;
 use favlist_core::inventory::{
-    diff_new_entries, find_missing_videos, scan_directory_bvids, write_inventory_file,
+    diff_changes, row_bvid, row_status, scan_directory_bvids, write_inventory_file,
+    REMOVED_ARCHIVE_DIR,
 };
 use favlist_core::{
-    current_timestamp, export_favlist_blocking, read_csv_rows, CsvRow, ExportOptions,
-    ExportProgress, ProgressCallback,
+    current_timestamp, export_favlist_blocking, folder_output_path, folder_url,
+    list_source_folders_blocking, login_with_qr_blocking, migrate_csv, parse_media_id,
+    parse_source_ref, read_csv_rows, refresh_credentials_file_blocking, render_qr,
+    resolve_source_blocking, sync_folders_blocking, BiliFavClient, CancellationToken, CsvRow,
+    DownloadState, ExportError, ExportEvent, ExportFormat, ExportOptions, FavSource,
+    InvalidVideoPolicy, Library, LibraryVideo, LoginOptions, ProxyConfig, QrPollStatus,
+    RateLimiter, RetryPolicy, SourceRef, SyncCallback, SyncEvent, SyncJob, SyncOptions,
 };
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
-use bbdown::{run_bbdown, start_bbdown_serve, BbdownApiClient};
+use bbdown::{run_bbdown, start_bbdown_serve, BbdownApiClient, ServePool};
+use commands::{Command, RemovedAction};
 use config::{
     ConfigStore, FavConfig, GlobalDefaultsStore, DEFAULT_BBDOWN_URL, DEFAULT_POLL_INTERVAL_MS,
 };
+use interrupt::{sleep_unless_cancelled, Interrupt};
 use menu::{select_from_menu, MenuOutcome};
 use prompts::{pause_with_message, prompt_input};
+use schedule::Schedule;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "B 站收藏夹下载助手", long_about = None)]
 struct Cli {
     /// 自定义配置文件路径
-    #[arg(long = "config-path")]
+    #[arg(long = "config-path", global = true)]
     config_path: Option<PathBuf>,
 
     /// Dry-run 模式，仅打印将执行的命令
-    #[arg(long = "dry-run")]
+    #[arg(long = "dry-run", global = true)]
     dry_run: bool,
+
+    /// 不带子命令时进入交互式菜单
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
 struct App {
     store: ConfigStore,
     defaults: GlobalDefaultsStore,
     dry_run: bool,
+    rate_limiter: Option<RateLimiter>,
+    library: Library,
+    interrupt: Interrupt,
+    /// 子命令模式下不暂停等待回车，也不切换终端原始模式。
+    interactive: bool,
+    /// 子命令指定的已取消收藏文件处理方式，为空时交互询问（非交互时保留）。
+    removed_action: Option<RemovedAction>,
+    /// 自动启动的 bbdown serve，Ctrl+C 强制退出前也会关闭。
+    serve_pool: ServePool,
+    /// 守护模式下在各轮之间保留 bbdown serve 供复用，否则检查缺漏后即关闭。
+    keep_serve: bool,
 }
 
 impl App {
-    fn new(store: ConfigStore, defaults: GlobalDefaultsStore, dry_run: bool) -> Self {
+    fn new(
+        store: ConfigStore,
+        defaults: GlobalDefaultsStore,
+        library: Library,
+        interrupt: Interrupt,
+        dry_run: bool,
+        interactive: bool,
+    ) -> Self {
+        let rate_limiter = defaults.data().rate_limiter();
+        let serve_pool = ServePool::default();
+        let pool = serve_pool.clone();
+        interrupt.on_force_exit(move || {
+            pool.stop_all();
+        });
         Self {
             store,
             defaults,
             dry_run,
+            rate_limiter,
+            library,
+            interrupt,
+            interactive,
+            removed_action: None,
+            serve_pool,
+            keep_serve: false,
+        }
+    }
+
+    fn pause(&self, message: &str) -> Result<()> {
+        if self.interactive {
+            pause_with_message(message)?;
+        }
+        Ok(())
+    }
+
+    /// 返回菜单前恢复原始模式。
+    fn resume_menu(&self) {
+        if self.interactive {
+            terminal::enable_raw_mode().ok();
         }
     }
 
@@ -63,6 +125,8 @@ impl App {
             match action {
                 MainAction::NewConfig => self.handle_new_config()?,
                 MainAction::UseConfig => self.handle_existing_configs()?,
+                MainAction::SyncAll => self.sync_all()?,
+                MainAction::Login => self.handle_login()?,
                 MainAction::EditDefaults => self.handle_global_defaults()?,
                 MainAction::Exit => {
                     println!("已退出助手。");
@@ -77,13 +141,17 @@ impl App {
         let options = vec![
             "录入新收藏夹".to_string(),
             "使用存档配置".to_string(),
+            "同步全部收藏夹".to_string(),
+            "扫码登录".to_string(),
             "设置全局默认".to_string(),
             "退出程序".to_string(),
         ];
         match select_from_menu("请选择操作", &options)? {
             MenuOutcome::Selected(0) => Ok(MainAction::NewConfig),
             MenuOutcome::Selected(1) => Ok(MainAction::UseConfig),
-            MenuOutcome::Selected(2) => Ok(MainAction::EditDefaults),
+            MenuOutcome::Selected(2) => Ok(MainAction::SyncAll),
+            MenuOutcome::Selected(3) => Ok(MainAction::Login),
+            MenuOutcome::Selected(4) => Ok(MainAction::EditDefaults),
             MenuOutcome::Selected(_) | MenuOutcome::Esc => Ok(MainAction::Exit),
         }
     }
@@ -92,13 +160,26 @@ impl App {
         terminal::disable_raw_mode().ok();
         println!("录入新收藏夹（留空可取消）");
 
-        let fav_url = prompt_input("请输入收藏夹 URL", None)?;
+        let fav_url = prompt_input(
+            "请输入收藏夹 / 合集 / 系列 / 稍后再看 URL（也可输入数字ID、ml号或b23.tv短链接）",
+            None,
+        )?;
         if fav_url.trim().is_empty() {
             println!("已取消录入。");
-            pause_with_message("按回车返回菜单...")?;
-            terminal::enable_raw_mode().ok();
+            self.pause("按回车返回菜单...")?;
+            self.resume_menu();
             return Ok(());
         }
+        let (fav_url, source) =
+            match canonical_fav_url(&fav_url, self.defaults.data().proxy_config()) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    println!("{}", style(format!("{err:#}")).red());
+                    self.pause("按回车返回菜单...")?;
+                    self.resume_menu();
+                    return Ok(());
+                }
+            };
 
         let defaults_snapshot = self.defaults.data().clone();
         let env_dir = std::env::current_dir()
@@ -138,7 +219,29 @@ impl App {
         let page_size = page_size_input.trim().parse::<u32>().unwrap_or(40).max(1);
         let timeout_input = prompt_input("请求超时(秒, 默认10)", Some("10"))?;
         let timeout_secs = timeout_input.trim().parse::<u64>().unwrap_or(10).max(1);
-        let cookie = normalize_optional(prompt_input("Cookie(可留空)", None)?);
+        let cookie_file = normalize_optional(prompt_input(
+            "Cookie 文件(cookies.txt 或 BBDown.data 路径，可留空)",
+            None,
+        )?);
+        let cookie = if cookie_file.is_some() {
+            None
+        } else {
+            let cookie_prompt = if source.requires_cookie() {
+                "Cookie(稍后再看/历史记录必填)"
+            } else {
+                "Cookie(可留空)"
+            };
+            normalize_optional(prompt_input(cookie_prompt, None)?)
+        };
+        if cookie.is_none() && cookie_file.is_none() && source.requires_cookie() {
+            println!("{}", style("未填写 Cookie，抓取该列表将会失败。").yellow());
+        }
+        let proxy_hint = defaults_snapshot.proxy.as_deref().unwrap_or("不使用代理");
+        let proxy_input = prompt_input(
+            &format!("请求代理(http/https/socks5，可含账号密码，留空沿用全局默认：{proxy_hint})"),
+            None,
+        )?;
+        let proxy = parse_proxy_input(&proxy_input, None);
         let name = normalize_optional(prompt_input("配置名称(可留空)", None)?);
 
         let file_pattern = resolve_optional_with_default(
@@ -203,70 +306,164 @@ impl App {
             .parse::<u64>()
             .unwrap_or(DEFAULT_POLL_INTERVAL_MS)
             .max(50);
+        let incremental_input = prompt_input("检查更新时使用增量同步? (y/N)", Some("N"))?;
+        let incremental = parse_bool_input(&incremental_input, false);
+
+        let space_url = matches!(source, FavSource::UserFolders { .. }).then(|| fav_url.clone());
+        let config = FavConfig {
+            fav_url,
+            legacy_download_dir: None,
+            api_download_dir,
+            scan_download_dir: Some(scan_download_dir.clone()),
+            bbdown_work_dir,
+            bbdown_max_concurrency,
+            csv_path: String::new(),
+            encoding,
+            page_size,
+            cookie,
+            cookie_file,
+            timeout_secs,
+            last_synced_at: None,
+            name,
+            bbdown_serve_url,
+            bbdown_auto_launch,
+            bbdown_launch_args,
+            bbdown_poll_interval_ms,
+            file_pattern,
+            multi_file_pattern,
+            incremental,
+            last_fav_time: None,
+            space_url,
+            proxy,
+            bbdown_bypass_proxy: None,
+            base_url: None,
+            schedule: None,
+        };
+
+        if config.space_url.is_some() {
+            println!("检测到用户空间链接，将登记该用户创建的全部收藏夹。");
+            if let Err(err) = self.register_user_folders(&config) {
+                println!("登记收藏夹失败: {err}");
+            }
+        } else if let Err(err) = self.export_new_config(config) {
+            println!("抓取失败: {err}");
+        }
 
+        self.pause("按回车返回菜单...")?;
+        self.resume_menu();
+        Ok(())
+    }
+
+    /// 首次抓取收藏夹并保存为新配置，被中断时不保存并返回 `false`。
+    fn export_new_config(&mut self, mut config: FavConfig) -> Result<bool> {
         let timestamp = current_timestamp();
-        let csv_path = Path::new(&scan_download_dir).join(format!("{timestamp}-favlist.csv"));
+        let mut csv_path =
+            Path::new(config.scan_download_dir()).join(format!("{timestamp}-favlist.csv"));
+        if config.space_url.is_some() {
+            csv_path = folder_output_path(&csv_path, parse_media_id(&config.fav_url)?);
+        }
 
         let options = ExportOptions {
-            fav_url: fav_url.clone(),
-            csv_path: csv_path.clone(),
-            encoding: encoding.clone(),
-            page_size,
-            cookie: cookie.clone(),
-            timeout_secs,
-            timestamp: Some(timestamp.clone()),
+            fav_url: config.fav_url.clone(),
+            csv_path,
+            format: ExportFormat::Csv,
+            encoding: config.encoding.clone(),
+            page_size: config.page_size,
+            cookie: config.inline_cookie(),
+            cookie_file: config.cookie_file_path(),
+            timeout_secs: config.timeout_secs,
+            timestamp: Some(timestamp),
             extra_headers: Default::default(),
-            base_url: None,
-            progress_callback: None,
+            base_url: config.api_base_url(self.defaults.data()),
+            proxy: config.proxy_config(self.defaults.data()),
+            short_link_base: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: self.rate_limiter.clone(),
+            fixtures: None,
+            incremental: None,
+            invalid_policy: InvalidVideoPolicy::Include,
+            event_callback: None,
+            cancel: None,
         };
 
-        match self.run_export_with_progress(options, "抓取收藏夹进度") {
-            Ok(result) => {
-                println!(
-                    "抓取完成，共新增 {} 条记录，输出文件：{}",
-                    style(result.new_entries.len()).green(),
-                    result.csv_path.display()
-                );
-                let mut config = FavConfig {
-                    fav_url,
-                    legacy_download_dir: None,
-                    api_download_dir,
-                    scan_download_dir: Some(scan_download_dir.clone()),
-                    bbdown_work_dir,
-                    bbdown_max_concurrency,
-                    csv_path: result.csv_path.display().to_string(),
-                    encoding,
-                    page_size,
-                    cookie,
-                    timeout_secs,
-                    last_synced_at: Some(result.timestamp),
-                    name,
-                    bbdown_serve_url,
-                    bbdown_auto_launch,
-                    bbdown_launch_args,
-                    bbdown_poll_interval_ms,
-                    file_pattern,
-                    multi_file_pattern,
-                };
-                config.apply_defaults();
-                self.store.add(config)?;
+        let result = match self.run_export_with_progress(options, "抓取收藏夹进度") {
+            Ok(result) => result,
+            Err(err) if is_cancelled(&err) => {
+                println!("{}", style(format!("{err}，未保存该配置。")).yellow());
+                return Ok(false);
             }
-            Err(err) => {
-                println!("抓取失败: {err}");
+            Err(err) => return Err(err),
+        };
+        println!(
+            "抓取完成，共新增 {} 条记录，输出文件：{}",
+            style(result.new_entries.len()).green(),
+            result.csv_path.display()
+        );
+        config.csv_path = result.csv_path.display().to_string();
+        config.last_synced_at = Some(result.timestamp);
+        config.last_fav_time = result.latest_fav_time;
+        config.apply_defaults();
+        self.store.add(config)?;
+        Ok(true)
+    }
+
+    /// 按模板为用户空间下尚未登记的收藏夹逐个创建配置，返回新登记的数量。
+    fn register_user_folders(&mut self, template: &FavConfig) -> Result<usize> {
+        let Some(space_url) = template.space_url.clone() else {
+            return Ok(0);
+        };
+        let options = ExportOptions {
+            fav_url: space_url,
+            cookie: template.inline_cookie(),
+            cookie_file: template.cookie_file_path(),
+            timeout_secs: template.timeout_secs,
+            base_url: template.api_base_url(self.defaults.data()),
+            proxy: template.proxy_config(self.defaults.data()),
+            rate_limiter: self.rate_limiter.clone(),
+            ..Default::default()
+        };
+        let folders = list_source_folders_blocking(&options)?;
+        let registered: HashSet<i64> = self
+            .store
+            .configs()
+            .iter()
+            .filter_map(|config| parse_media_id(&config.fav_url).ok())
+            .collect();
+
+        let mut added = 0usize;
+        for folder in folders {
+            if registered.contains(&folder.media_id) {
+                continue;
+            }
+            println!(
+                "{}",
+                style(format!(
+                    "登记收藏夹「{}」({})",
+                    folder.title, folder.media_id
+                ))
+                .cyan()
+            );
+            let mut config = template.clone();
+            config.fav_url = folder_url(folder.mid, folder.media_id);
+            config.name = Some(match template.name.as_deref() {
+                Some(prefix) if !prefix.is_empty() => format!("{prefix} / {}", folder.title),
+                _ => folder.title.clone(),
+            });
+            match self.export_new_config(config) {
+                Ok(true) => added += 1,
+                Ok(false) => break,
+                Err(err) => println!("收藏夹「{}」抓取失败: {err}", folder.title),
             }
         }
-
-        pause_with_message("按回车返回菜单...")?;
-        terminal::enable_raw_mode().ok();
-        Ok(())
+        Ok(added)
     }
 
     fn handle_existing_configs(&mut self) -> Result<()> {
         if self.store.configs().is_empty() {
             terminal::disable_raw_mode().ok();
             println!("当前没有任何已保存的配置。可先录入新收藏夹。");
-            pause_with_message("按回车返回菜单...")?;
-            terminal::enable_raw_mode().ok();
+            self.pause("按回车返回菜单...")?;
+            self.resume_menu();
             return Ok(());
         }
 
@@ -275,15 +472,7 @@ impl App {
             .configs()
             .iter()
             .enumerate()
-            .map(|(idx, cfg)| {
-                let title = cfg
-                    .name
-                    .as_ref()
-                    .filter(|name| !name.is_empty())
-                    .cloned()
-                    .unwrap_or_else(|| format!("收藏夹 {}", idx + 1));
-                format!("{} -> {}", title, cfg.fav_url)
-            })
+            .map(|(idx, cfg)| format!("{} -> {}", config_label(cfg, idx), cfg.fav_url))
             .collect();
 
         match select_from_menu("选择配置", &options)? {
@@ -298,31 +487,91 @@ impl App {
         mut options: ExportOptions,
         label: &str,
     ) -> Result<favlist_core::ExportResult> {
-        let display_label = label.to_string();
         let progress_bar = ProgressBar::new_spinner();
-        progress_bar.set_style(
-            ProgressStyle::with_template("{spinner:.green} {msg}")
-                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
-        );
-        progress_bar.set_message(display_label.clone());
+        progress_bar.set_style(export_spinner_style());
+        progress_bar.set_prefix(label.to_string());
+        progress_bar.set_message("正在解析链接");
         progress_bar.enable_steady_tick(Duration::from_millis(120));
-        let spinner = progress_bar.clone();
-        let label_clone = display_label.clone();
-        options.progress_callback = Some(Arc::new(move |progress: ExportProgress| {
-            if let Some(total) = progress.total {
-                spinner.set_message(format!(
-                    "{}：已获取 {}/{}",
-                    label_clone, progress.current, total
-                ));
-            } else {
-                spinner.set_message(format!("{}：已获取 {} 条", label_clone, progress.current));
-            }
-        }) as ProgressCallback);
+        let bar = progress_bar.clone();
+        options.event_callback = Some(Arc::new(move |event: ExportEvent| {
+            apply_export_event(&bar, &event);
+        }));
+        let scope = self.interrupt.begin();
+        options.cancel = Some(scope.token());
         let result = export_favlist_blocking(options).map_err(|err| err.into());
         progress_bar.finish_and_clear();
         result
     }
 
+    /// 扫码登录并保存凭据，可选择让尚未指定 Cookie 文件的配置改用该凭据。
+    fn handle_login(&mut self) -> Result<()> {
+        terminal::disable_raw_mode().ok();
+        let credentials_path = self.store.credentials_path();
+        let outcome = login_with_qr_blocking(
+            LoginOptions {
+                proxy: self.defaults.data().proxy_config(),
+                ..Default::default()
+            },
+            |ticket| match render_qr(&ticket.url) {
+                Ok(qr) => {
+                    println!("{qr}");
+                    println!("请使用哔哩哔哩客户端扫码登录，或打开链接：{}", ticket.url);
+                }
+                Err(err) => println!(
+                    "{}",
+                    style(format!("{err}，请打开链接：{}", ticket.url)).yellow()
+                ),
+            },
+            |status| {
+                if matches!(status, QrPollStatus::Scanned) {
+                    println!("已扫码，请在手机上确认登录...");
+                }
+            },
+        );
+        match outcome {
+            Ok(credentials) => {
+                credentials.save(&credentials_path)?;
+                println!(
+                    "{}",
+                    style(format!(
+                        "登录成功，凭据已保存至 {}",
+                        credentials_path.display()
+                    ))
+                    .green()
+                );
+                let pending: Vec<usize> = self
+                    .store
+                    .configs()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, config)| config.cookie_file.is_none())
+                    .map(|(index, _)| index)
+                    .collect();
+                if !pending.is_empty() {
+                    let answer = prompt_input(
+                        &format!(
+                            "将 {} 个未指定 Cookie 文件的配置改用该凭据? (Y/n)",
+                            pending.len()
+                        ),
+                        Some("Y"),
+                    )?;
+                    if parse_bool_input(&answer, true) {
+                        for index in pending {
+                            let mut config = self.store.configs()[index].clone();
+                            config.cookie_file = Some(credentials_path.display().to_string());
+                            config.cookie = None;
+                            self.store.update(index, config)?;
+                        }
+                    }
+                }
+            }
+            Err(err) => println!("{}", style(format!("{err}")).red()),
+        }
+        self.pause("按回车返回菜单...")?;
+        self.resume_menu();
+        Ok(())
+    }
+
     fn handle_global_defaults(&mut self) -> Result<()> {
         terminal::disable_raw_mode().ok();
         println!("设置全局默认（留空保留原值，输入 '-' 清除）");
@@ -354,6 +603,29 @@ impl App {
         let concurrency_prompt = snapshot.bbdown_max_concurrency.map(|v| v.to_string());
         let concurrency_input =
             prompt_input("默认下载任务并发数量(>=1)", concurrency_prompt.as_deref())?;
+        let rps_prompt = snapshot.requests_per_second.map(|v| v.to_string());
+        let rps_input = prompt_input("默认请求速率上限(次/秒)", rps_prompt.as_deref())?;
+        let sync_prompt = snapshot.sync_concurrency.map(|v| v.to_string());
+        let sync_input = prompt_input(
+            "同步全部时同时抓取的收藏夹数量(>=1)",
+            sync_prompt.as_deref(),
+        )?;
+        let proxy_input = prompt_input(
+            "默认请求代理(http/https/socks5 地址，可含账号密码)",
+            snapshot.proxy.as_deref(),
+        )?;
+        let bypass_input = prompt_input(
+            "访问 bbdown serve 时绕过代理? (Y/n)",
+            Some(if snapshot.bbdown_bypass_proxy.unwrap_or(true) {
+                "Y"
+            } else {
+                "n"
+            }),
+        )?;
+        let base_url_input = prompt_input(
+            "默认B站 API 地址(留空使用官方接口，可指向模拟服务器)",
+            snapshot.base_url.as_deref(),
+        )?;
 
         {
             let data = self.defaults.data_mut();
@@ -391,12 +663,41 @@ impl App {
             };
             data.bbdown_max_concurrency =
                 parse_concurrency_input(&concurrency_input, snapshot.bbdown_max_concurrency);
+            data.requests_per_second = match rps_input.trim() {
+                "" => snapshot.requests_per_second,
+                "-" => None,
+                other => other
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| *value > 0.0)
+                    .or(snapshot.requests_per_second),
+            };
+            data.sync_concurrency = match sync_input.trim() {
+                "" => snapshot.sync_concurrency,
+                "-" => None,
+                other => other
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|value| *value >= 1)
+                    .or(snapshot.sync_concurrency),
+            };
+            data.proxy = parse_proxy_input(&proxy_input, snapshot.proxy);
+            data.bbdown_bypass_proxy = Some(parse_bool_input(
+                &bypass_input,
+                snapshot.bbdown_bypass_proxy.unwrap_or(true),
+            ));
+            data.base_url = match base_url_input.trim() {
+                "" => snapshot.base_url,
+                "-" => None,
+                other => Some(other.trim_end_matches('/').to_string()),
+            };
         }
 
         self.defaults.save()?;
+        self.rate_limiter = self.defaults.data().rate_limiter();
         println!("{}", style("全局默认已更新。").green());
-        pause_with_message("按回车返回菜单...")?;
-        terminal::enable_raw_mode().ok();
+        self.pause("按回车返回菜单...")?;
+        self.resume_menu();
         Ok(())
     }
 
@@ -437,7 +738,8 @@ impl App {
 
         let fav_url = prompt_input("收藏夹 URL", Some(&config.fav_url))?;
         if !fav_url.is_empty() {
-            config.fav_url = fav_url;
+            config.fav_url =
+                canonical_fav_url(&fav_url, config.proxy_config(self.defaults.data()))?.0;
         }
 
         let csv_path = prompt_input("CSV 路径", Some(&config.csv_path))?;
@@ -463,11 +765,25 @@ impl App {
                 .max(1);
         }
 
-        let cookie = prompt_input("Cookie (- 表示清除)", config.cookie.as_deref())?;
-        if cookie == "-" {
+        let cookie_file = prompt_input(
+            "Cookie 文件(cookies.txt/BBDown.data，- 表示清除)",
+            config.cookie_file.as_deref(),
+        )?;
+        if cookie_file == "-" {
+            config.cookie_file = None;
+        } else if !cookie_file.is_empty() {
+            config.cookie_file = Some(cookie_file);
+            // 改用 Cookie 文件后不再保留明文 Cookie。
             config.cookie = None;
-        } else if !cookie.is_empty() {
-            config.cookie = Some(cookie);
+        }
+
+        if config.cookie_file.is_none() {
+            let cookie = prompt_input("Cookie (- 表示清除)", config.cookie.as_deref())?;
+            if cookie == "-" {
+                config.cookie = None;
+            } else if !cookie.is_empty() {
+                config.cookie = Some(cookie);
+            }
         }
 
         let name = prompt_input("展示名称 (- 表示清除)", config.name.as_deref())?;
@@ -477,10 +793,31 @@ impl App {
             config.name = Some(name);
         }
 
+        let proxy_input = prompt_input("请求代理 (- 表示沿用全局默认)", config.proxy.as_deref())?;
+        config.proxy = parse_proxy_input(&proxy_input, config.proxy.clone());
+        let base_url_input = prompt_input(
+            "B站 API 地址 (- 表示沿用全局默认)",
+            config.base_url.as_deref(),
+        )?;
+        config.base_url = match base_url_input.trim() {
+            "" => config.base_url.clone(),
+            "-" => None,
+            other => Some(other.trim_end_matches('/').to_string()),
+        };
+
         let serve_url = prompt_input("BBDown serve 地址", Some(&config.bbdown_serve_url))?;
         if !serve_url.trim().is_empty() {
             config.bbdown_serve_url = serve_url.trim().to_string();
         }
+        let bypass_default = config
+            .bbdown_bypass_proxy
+            .or(self.defaults.data().bbdown_bypass_proxy)
+            .unwrap_or(true);
+        let bypass_input = prompt_input(
+            "访问 BBDown serve 时绕过代理? (Y/n)",
+            Some(if bypass_default { "Y" } else { "n" }),
+        )?;
+        config.bbdown_bypass_proxy = Some(parse_bool_input(&bypass_input, bypass_default));
 
         let auto_launch_input = prompt_input(
             "自动启动BBDown serve? (Y/n)",
@@ -548,18 +885,171 @@ impl App {
         )?;
         config.bbdown_max_concurrency =
             parse_concurrency_input(&concurrency_input, config.bbdown_max_concurrency);
+        let incremental_input = prompt_input(
+            "检查更新时使用增量同步? (y/n)",
+            Some(if config.incremental { "y" } else { "n" }),
+        )?;
+        config.incremental = parse_bool_input(&incremental_input, config.incremental);
+        let schedule_input = prompt_input(
+            "守护模式同步计划(间隔如 6h 或 cron 表达式，- 表示清除)",
+            config.schedule.as_deref(),
+        )?;
+        match schedule_input.trim() {
+            "" => {}
+            "-" => config.schedule = None,
+            input => match input.parse::<Schedule>() {
+                Ok(_) => config.schedule = Some(input.to_string()),
+                Err(err) => println!("{}", style(format!("{err}，保持原值。")).yellow()),
+            },
+        }
 
         self.store.update(index, config)?;
 
-        pause_with_message("配置已更新，按回车返回...")?;
-        terminal::enable_raw_mode().ok();
+        self.pause("配置已更新，按回车返回...")?;
+        self.resume_menu();
         Ok(())
     }
 
     fn check_update(&mut self, index: usize) -> Result<()> {
-        let mut config = self.store.configs()[index].clone();
         terminal::disable_raw_mode().ok();
         println!("检查更新...");
+        let (pending, options) = self.prepare_update(index, &mut HashSet::new())?;
+        let result = self.run_export_with_progress(options, "检查更新进度");
+        self.finish_update(pending, result)?;
+        self.pause("按回车返回...")?;
+        self.resume_menu();
+        Ok(())
+    }
+
+    fn sync_all(&mut self) -> Result<()> {
+        terminal::disable_raw_mode().ok();
+        let count = self.store.configs().len();
+        if count == 0 {
+            println!("当前没有任何已保存的配置。可先录入新收藏夹。");
+        } else {
+            self.sync_configs((0..count).collect())?;
+        }
+        self.pause("按回车返回菜单...")?;
+        self.resume_menu();
+        Ok(())
+    }
+
+    /// 并发抓取指定配置，再逐个比对变更并下载新增条目。
+    /// Cookie、代理等客户端设置相同的配置共用一个客户端，不同的分组依次执行。
+    fn sync_configs(&mut self, indices: Vec<usize>) -> Result<SyncSummary> {
+        let concurrency = self.defaults.data().sync_concurrency();
+        println!("同步 {} 个收藏夹（并发 {concurrency}）...", indices.len());
+
+        let scope = self.interrupt.begin();
+        let mut summary = SyncSummary::default();
+        let mut pending = Vec::new();
+        let mut claimed = HashSet::new();
+        let mut groups: Vec<(ClientKey, Vec<usize>, Vec<SyncJob>)> = Vec::new();
+        for index in indices {
+            let label = config_label(&self.store.configs()[index], index);
+            match self.prepare_update(index, &mut claimed) {
+                Ok((update, mut options)) => {
+                    options.cancel = Some(scope.token());
+                    let key = ClientKey::of(&options);
+                    let position = pending.len();
+                    pending.push(update);
+                    let job = SyncJob::new(label, options);
+                    match groups.iter_mut().find(|(existing, _, _)| *existing == key) {
+                        Some((_, positions, jobs)) => {
+                            positions.push(position);
+                            jobs.push(job);
+                        }
+                        None => groups.push((key, vec![position], vec![job])),
+                    }
+                }
+                Err(err) => {
+                    summary.failed += 1;
+                    println!("{}", style(format!("「{label}」准备失败: {err}")).red());
+                }
+            }
+        }
+
+        let mut results: Vec<Option<Result<favlist_core::ExportResult>>> =
+            (0..pending.len()).map(|_| None).collect();
+        for (_, positions, jobs) in groups {
+            if scope.is_cancelled() {
+                break;
+            }
+            let client = BiliFavClient::new(jobs[0].options.client_options())?;
+            let report = sync_folders_blocking(
+                &client,
+                jobs,
+                SyncOptions {
+                    max_concurrency: concurrency,
+                    callback: Some(sync_progress_callback()),
+                },
+            )?;
+            for (position, outcome) in positions.into_iter().zip(report.outcomes) {
+                results[position] = Some(outcome.result.map_err(Into::into));
+            }
+        }
+
+        for (update, result) in pending.into_iter().zip(results) {
+            let mut result = result.unwrap_or_else(|| Err(anyhow!("未执行")));
+            // 中断后不再下载已抓取完成的收藏夹，统一恢复旧 CSV，下次同步时重新处理。
+            if scope.is_cancelled() && result.is_ok() {
+                result = Err(anyhow!("同步已中断"));
+            }
+            let label = config_label(&update.config, update.index);
+            println!("{}", style(format!("== {label} ==")).cyan());
+            let exported = result.is_ok();
+            match self.finish_update(update, result) {
+                Ok(()) if exported => summary.succeeded += 1,
+                Ok(()) => summary.failed += 1,
+                Err(err) => {
+                    summary.failed += 1;
+                    println!("{}", style(format!("处理失败: {err}")).red());
+                }
+            }
+        }
+        summary.interrupted = scope.is_cancelled();
+        println!(
+            "同步完成：成功 {}，失败 {}",
+            style(summary.succeeded).green(),
+            style(summary.failed).red()
+        );
+        Ok(summary)
+    }
+
+    /// 检查更新的准备阶段：登记新收藏夹、备份旧 CSV 并生成导出参数。
+    /// `claimed` 记录本轮已分配的输出路径，同一路径不会分给两个任务。
+    fn prepare_update(
+        &mut self,
+        index: usize,
+        claimed: &mut HashSet<PathBuf>,
+    ) -> Result<(PendingUpdate, ExportOptions)> {
+        let config = self.store.configs()[index].clone();
+        if config.space_url.is_some() {
+            // 新收藏夹沿用当前配置的下载设置，名称直接取收藏夹标题。
+            let template = FavConfig {
+                name: None,
+                ..config.clone()
+            };
+            match self.register_user_folders(&template) {
+                Ok(0) => {}
+                Ok(count) => println!("已自动登记 {count} 个新建的收藏夹。"),
+                Err(err) => println!("发现新收藏夹失败: {err}"),
+            }
+        }
+
+        let media_id = parse_media_id(&config.fav_url)?;
+        self.seed_library(&config, media_id)?;
+        // 同一秒内准备的多个配置可能共用下载目录，文件名带上列表 ID 以免互相覆盖。
+        let timestamp = current_timestamp();
+        let new_csv_path = folder_output_path(
+            &config
+                .scan_download_dir_path()
+                .join(format!("{timestamp}-favlist.csv")),
+            media_id,
+        );
+        if new_csv_path.exists() || !claimed.insert(new_csv_path.clone()) {
+            bail!("输出文件已被占用: {}", new_csv_path.display());
+        }
 
         let old_csv_path = config.csv_path();
         let old_rows = read_csv_rows(&old_csv_path, &config.encoding).unwrap_or_default();
@@ -570,87 +1060,328 @@ impl App {
                 .with_context(|| format!("备份旧 CSV 失败: {}", backup_path.display()))?;
             println!("旧 CSV 已备份至 {}", backup_path.display());
         }
-
-        let timestamp = current_timestamp();
-        let new_csv_path = config
-            .scan_download_dir_path()
-            .join(format!("{timestamp}-favlist.csv"));
+        // 增量模式只抓取最新几页，需要以旧 CSV 为基础继续追加。
+        let incremental = if had_old_file {
+            config.incremental_options()
+        } else {
+            None
+        };
+        let full_scan = incremental.is_none();
 
         let options = ExportOptions {
             fav_url: config.fav_url.clone(),
             csv_path: new_csv_path.clone(),
+            format: ExportFormat::Csv,
             encoding: config.encoding.clone(),
             page_size: config.page_size,
-            cookie: config.cookie.clone(),
+            cookie: config.inline_cookie(),
+            cookie_file: config.cookie_file_path(),
             timeout_secs: config.timeout_secs,
             timestamp: Some(timestamp.clone()),
             extra_headers: Default::default(),
-            base_url: None,
-            progress_callback: None,
+            base_url: config.api_base_url(self.defaults.data()),
+            proxy: config.proxy_config(self.defaults.data()),
+            short_link_base: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: self.rate_limiter.clone(),
+            fixtures: None,
+            incremental,
+            invalid_policy: InvalidVideoPolicy::Include,
+            event_callback: None,
+            cancel: None,
         };
 
-        match self.run_export_with_progress(options, "检查更新进度") {
-            Ok(result) => {
-                let new_rows = read_csv_rows(&new_csv_path, &config.encoding)?;
-                let diffs = diff_new_entries(&old_rows, &new_rows);
-                if diffs.is_empty() {
-                    println!("未发现新增条目。");
+        let pending = PendingUpdate {
+            index,
+            config,
+            media_id,
+            old_csv_path,
+            old_rows,
+            backup_path,
+            had_old_file,
+            new_csv_path,
+            full_scan,
+        };
+        if !full_scan {
+            if let Err(err) = fs::copy(&pending.backup_path, &pending.new_csv_path) {
+                pending.rollback()?;
+                return Err(err).with_context(|| {
+                    format!("复制旧 CSV 失败: {}", pending.new_csv_path.display())
+                });
+            }
+        }
+        Ok((pending, options))
+    }
+
+    /// 检查更新的收尾阶段：比对变更、记录视频库并下载新增条目；导出失败时恢复旧 CSV。
+    fn finish_update(
+        &mut self,
+        pending: PendingUpdate,
+        result: Result<favlist_core::ExportResult>,
+    ) -> Result<()> {
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                if is_cancelled(&err) {
+                    println!("{}", style(format!("{err}，已恢复旧 CSV。")).yellow());
                 } else {
-                    println!("发现 {} 个新增条目：", diffs.len());
-                    let download_dir = config.scan_download_dir_path();
-                    fs::create_dir_all(&download_dir)?;
-                    let work_dir = config
-                        .resolve_work_dir(self.defaults.data())
-                        .unwrap_or_else(|| download_dir.clone());
-                    let mut count = 0;
-                    for row in diffs {
-                        if let Some(bvid) = extract_bvid(&row) {
-                            println!("下载 {}", bvid);
-                            if let Err(err) = run_bbdown(&bvid, &work_dir, self.dry_run) {
-                                println!("bbdown 失败: {err}");
-                            }
-                            count += 1;
-                        }
+                    println!("导出失败: {err}");
+                }
+                pending.rollback()?;
+                return Ok(());
+            }
+        };
+        let PendingUpdate {
+            index,
+            mut config,
+            media_id,
+            old_rows,
+            new_csv_path,
+            full_scan,
+            ..
+        } = pending;
+        if result.stopped_early {
+            println!("增量同步：已遇到已同步条目，提前结束翻页。");
+        }
+        let new_rows = read_csv_rows(&new_csv_path, &config.encoding)?;
+        let changes = diff_changes(&old_rows, &new_rows);
+        for change in &changes.title_changed {
+            println!(
+                "标题变化 {}：{} → {}",
+                change.bv_id, change.old_title, change.new_title
+            );
+        }
+        for row in &changes.became_invalid {
+            if let Some(bvid) = row_bvid(row) {
+                println!(
+                    "{}",
+                    style(format!(
+                        "条目已失效 {} ({})",
+                        bvid,
+                        row_status(row).as_str()
+                    ))
+                    .red()
+                );
+            }
+        }
+        if full_scan {
+            if !changes.removed.is_empty() {
+                self.handle_removed_videos(&config, media_id, &changes.removed)?;
+            }
+        } else {
+            println!("增量模式不检测取消收藏的条目。");
+        }
+        let sync = self.library.record_folder(
+            media_id,
+            &result.folder_info.title,
+            &result.seen_entries,
+            &result.timestamp,
+        )?;
+        let mut added = Vec::new();
+        for bvid in &sync.added {
+            if let Some(video) = self.library.video(bvid)? {
+                added.push(video);
+            }
+        }
+        let (diffs, invalid_diffs): (Vec<LibraryVideo>, Vec<LibraryVideo>) = added
+            .into_iter()
+            .partition(|video| video.status.is_available());
+        for video in &invalid_diffs {
+            println!("跳过失效条目 {} ({})", video.bv_id, video.status.as_str());
+        }
+        if diffs.is_empty() {
+            println!("未发现新增条目。");
+        } else {
+            println!("发现 {} 个新增条目：", diffs.len());
+            let download_dir = config.scan_download_dir_path();
+            fs::create_dir_all(&download_dir)?;
+            let work_dir = config
+                .resolve_work_dir(self.defaults.data())
+                .unwrap_or_else(|| download_dir.clone());
+            let mut count = 0;
+            for video in diffs {
+                println!("下载 {}", video.bv_id);
+                let state = match run_bbdown(&video.bv_id, &work_dir, self.dry_run) {
+                    Ok(()) => DownloadState::Downloaded,
+                    Err(err) => {
+                        println!("bbdown 失败: {err}");
+                        DownloadState::Failed
                     }
-                    println!("新增条目处理完成，总计 {} 个", count);
+                };
+                if !self.dry_run {
+                    self.library.set_download_state(&video.bv_id, state)?;
                 }
-                config.csv_path = new_csv_path.display().to_string();
-                config.last_synced_at = Some(result.timestamp);
-                self.store.update(index, config)?;
+                count += 1;
             }
-            Err(err) => {
-                println!("导出失败: {err}");
-                if had_old_file {
-                    fs::rename(&backup_path, &old_csv_path).with_context(|| "恢复旧 CSV 失败")?;
+            println!("新增条目处理完成，总计 {} 个", count);
+        }
+        config.csv_path = new_csv_path.display().to_string();
+        config.last_synced_at = Some(result.timestamp);
+        config.last_fav_time = match (config.last_fav_time, result.latest_fav_time) {
+            (Some(previous), Some(latest)) => Some(previous.max(latest)),
+            (previous, latest) => latest.or(previous),
+        };
+        self.store.update(index, config)?;
+        Ok(())
+    }
+
+    /// 处理已取消收藏的条目：移出视频库中的收藏夹，并按需归档或删除本地文件。
+    fn handle_removed_videos(
+        &mut self,
+        config: &FavConfig,
+        media_id: i64,
+        removed: &[CsvRow],
+    ) -> Result<()> {
+        println!(
+            "{}",
+            style(format!("{} 个条目已取消收藏：", removed.len())).yellow()
+        );
+        let mut bvids = Vec::new();
+        for row in removed {
+            if let Some(bvid) = row_bvid(row) {
+                let title = row.get("title").map(String::as_str).unwrap_or_default();
+                println!("• {} {}", style(&bvid).yellow(), title);
+                bvids.push(bvid);
+            }
+        }
+        if !self.dry_run {
+            self.library.remove_from_folder(media_id, &bvids)?;
+        }
+
+        let download_dir = config.scan_download_dir_path();
+        if !download_dir.exists() {
+            return Ok(());
+        }
+        let mapping = scan_directory_bvids(&download_dir)?;
+        let mut files = Vec::new();
+        for bvid in &bvids {
+            // 仍在其他收藏夹中的视频保留本地文件。
+            let shared = self
+                .library
+                .video(bvid)?
+                .is_some_and(|video| video.folders.iter().any(|folder| *folder != media_id));
+            if shared {
+                println!("{} 仍在其他收藏夹中，保留本地文件", bvid);
+                continue;
+            }
+            if let Some(paths) = mapping.get(bvid) {
+                files.extend(paths.iter().cloned());
+            }
+        }
+        if files.is_empty() {
+            println!("本地没有需要处理的文件。");
+            return Ok(());
+        }
+
+        println!("本地共有 {} 个相关文件：", files.len());
+        for path in &files {
+            println!("  {}", path.display());
+        }
+        let action = match self.removed_action {
+            Some(action) => action,
+            None if self.interactive => {
+                let input = prompt_input("处理方式: a=归档 d=删除 k=保留", Some("k"))?;
+                match input.trim().to_lowercase().as_str() {
+                    "a" => RemovedAction::Archive,
+                    "d" => RemovedAction::Delete,
+                    _ => RemovedAction::Keep,
+                }
+            }
+            None => RemovedAction::Keep,
+        };
+        match action {
+            RemovedAction::Archive => {
+                let archive_dir = download_dir.join(REMOVED_ARCHIVE_DIR);
+                if self.dry_run {
+                    println!("[dry-run] 将移动到 {}", archive_dir.display());
+                } else {
+                    archive_removed_files(&download_dir, &files)?;
+                    println!("已归档至 {}", archive_dir.display());
+                }
+            }
+            RemovedAction::Delete => {
+                // 子命令显式指定删除时不再确认。
+                let confirmed = self.removed_action.is_some()
+                    || parse_bool_input(
+                        &prompt_input(&format!("确认删除 {} 个文件? (y/N)", files.len()), None)?,
+                        false,
+                    );
+                if !confirmed {
+                    println!("已取消删除。");
+                } else if self.dry_run {
+                    println!("[dry-run] 将删除以上文件");
+                } else {
+                    for path in &files {
+                        fs::remove_file(path)
+                            .with_context(|| format!("删除失败: {}", path.display()))?;
+                    }
+                    println!("已删除 {} 个文件", files.len());
                 }
             }
+            RemovedAction::Keep => println!("已保留本地文件。"),
         }
+        if !self.dry_run {
+            let refreshed = scan_directory_bvids(&download_dir)?;
+            self.library.record_local_files(&download_dir, &refreshed)?;
+        }
+        Ok(())
+    }
 
-        pause_with_message("按回车返回...")?;
-        terminal::enable_raw_mode().ok();
+    /// 库中尚无该收藏夹时，先把现有 CSV 导入为历史记录，避免全部条目被当作新增。
+    fn seed_library(&mut self, config: &FavConfig, media_id: i64) -> Result<()> {
+        if self.library.has_folder(media_id)? {
+            return Ok(());
+        }
+        let csv_path = config.csv_path();
+        if !csv_path.exists() {
+            return Ok(());
+        }
+        migrate_csv(&csv_path, &config.encoding)?;
+        let rows = read_csv_rows(&csv_path, &config.encoding)?;
+        let folder_title = rows
+            .iter()
+            .filter_map(|row| row.get("fav_name"))
+            .find(|value| !value.trim().is_empty())
+            .cloned()
+            .or_else(|| config.name.clone())
+            .unwrap_or_default();
+        let sync = self
+            .library
+            .import_csv_rows(media_id, &folder_title, &rows)?;
+        if sync.seen > 0 {
+            println!(
+                "已从 {} 导入 {} 条历史记录到本地视频库",
+                csv_path.display(),
+                sync.seen
+            );
+        }
         Ok(())
     }
 
     fn check_missing(&mut self, index: usize) -> Result<()> {
-        let config = self.store.configs()[index].clone();
         terminal::disable_raw_mode().ok();
+        self.fill_missing(index)?;
+        self.pause("按回车返回...")?;
+        self.resume_menu();
+        Ok(())
+    }
+
+    /// 比对视频库与下载目录找出缺失条目，并提交给 bbdown serve 补全。
+    fn fill_missing(&mut self, index: usize) -> Result<MissingOutcome> {
+        let config = self.store.configs()[index].clone();
         println!("检查缺漏...");
 
         let download_dir = config.scan_download_dir_path();
         if !download_dir.exists() {
             println!("下载目录不存在: {}", download_dir.display());
-            pause_with_message("按回车返回...")?;
-            terminal::enable_raw_mode().ok();
-            return Ok(());
+            return Ok(MissingOutcome::Failed);
         }
 
         let mapping = match scan_directory_bvids(&download_dir) {
             Ok(data) => data,
             Err(err) => {
                 println!("扫描下载目录失败: {err}");
-                pause_with_message("按回车返回...")?;
-                terminal::enable_raw_mode().ok();
-                return Ok(());
+                return Ok(MissingOutcome::Failed);
             }
         };
 
@@ -659,225 +1390,340 @@ impl App {
             Err(err) => println!("生成目录清单失败: {err}"),
         }
 
-        let csv_rows = read_csv_rows(&config.csv_path(), &config.encoding)?;
-        let existing_bvids: Vec<String> = mapping.keys().cloned().collect();
-        let missing_rows = find_missing_videos(&csv_rows, &existing_bvids);
-        if missing_rows.is_empty() {
-            println!("{}", style("未检测到缺失的视频。").green());
-        } else {
+        match migrate_csv(&config.csv_path(), &config.encoding) {
+            Ok(Some(migration)) => println!(
+                "CSV 表头已从 v{} 升级，原文件备份于 {}",
+                migration.from_version,
+                migration.backup_path.display()
+            ),
+            Ok(None) => {}
+            Err(err) => println!("CSV 迁移失败: {err}"),
+        }
+        let media_id = parse_media_id(&config.fav_url)?;
+        self.seed_library(&config, media_id)?;
+        self.library.record_local_files(&download_dir, &mapping)?;
+        let videos = self.library.folder_videos(media_id)?;
+        let invalid: Vec<&LibraryVideo> = videos
+            .iter()
+            .filter(|video| !video.status.is_available() && video.local_paths.is_empty())
+            .collect();
+        if !invalid.is_empty() {
             println!(
                 "{}",
-                style(format!("检测到 {} 个缺失条目：", missing_rows.len())).yellow()
+                style(format!(
+                    "{} 个缺失条目已失效，不再尝试下载：",
+                    invalid.len()
+                ))
+                .red()
             );
-            for row in &missing_rows {
-                if let Some(bvid) = extract_bvid(row) {
-                    println!("• {}", style(bvid).cyan());
-                }
+            for video in &invalid {
+                println!(
+                    "• {} ({})",
+                    style(&video.bv_id).red(),
+                    video.status.as_str()
+                );
             }
+        }
+        let missing_bvids: Vec<String> = videos
+            .iter()
+            .filter(|video| video.is_missing())
+            .map(|video| video.bv_id.clone())
+            .collect();
+        if missing_bvids.is_empty() {
+            println!("{}", style("未检测到缺失的视频。").green());
+            return Ok(MissingOutcome::Complete);
+        }
+        println!(
+            "{}",
+            style(format!("检测到 {} 个缺失条目：", missing_bvids.len())).yellow()
+        );
+        for bvid in &missing_bvids {
+            println!("• {}", style(bvid).cyan());
+        }
 
-            let missing_bvids: Vec<String> = missing_rows.iter().filter_map(extract_bvid).collect();
-            let file_pattern = config.resolve_file_pattern();
-            let multi_file_pattern = config.resolve_multi_file_pattern();
-            let serve_url = config.effective_serve_url().to_string();
+        let file_pattern = config.resolve_file_pattern();
+        let multi_file_pattern = config.resolve_multi_file_pattern();
+        let serve_url = config.effective_serve_url().to_string();
 
-            if missing_bvids.is_empty() {
-                println!("{}", style("缺失列表中未找到有效的 BV 号。").red());
-            } else if self.dry_run {
+        if self.dry_run {
+            println!(
+                "{}",
+                style("当前为 dry-run 模式，将仅展示拟提交的任务与配置。").yellow()
+            );
+            println!("目标服务：{}", serve_url);
+            if let Some(pattern) = &file_pattern {
+                println!("FilePattern: {}", pattern);
+            }
+            if let Some(pattern) = &multi_file_pattern {
+                println!("MultiFilePattern: {}", pattern);
+            }
+            for bvid in &missing_bvids {
                 println!(
-                    "{}",
-                    style("当前为 dry-run 模式，将仅展示拟提交的任务与配置。").yellow()
+                    "[dry-run] POST {}/add-task {{ Url: \"{}\" }}",
+                    serve_url, bvid
                 );
-                println!("目标服务：{}", serve_url);
-                if let Some(pattern) = &file_pattern {
-                    println!("FilePattern: {}", pattern);
-                }
-                if let Some(pattern) = &multi_file_pattern {
-                    println!("MultiFilePattern: {}", pattern);
-                }
-                for bvid in &missing_bvids {
-                    println!(
-                        "[dry-run] POST {}/add-task {{ Url: \"{}\" }}",
-                        serve_url, bvid
-                    );
+            }
+            println!(
+                "{}",
+                style("未执行实际下载操作，目录状态保持不变。").yellow()
+            );
+            return Ok(MissingOutcome::Remaining(missing_bvids.len()));
+        }
+        let mut launched = false;
+        let kept = self.serve_pool.is_running(&serve_url);
+        if kept == Some(false) {
+            println!(
+                "{}",
+                style("保留的 bbdown serve 已退出，将重新启动。").yellow()
+            );
+        }
+        if kept == Some(true) {
+            println!("{}", style("沿用已启动的 bbdown serve。").green());
+            launched = true;
+        } else if config.bbdown_auto_launch {
+            let work_dir = config
+                .resolve_work_dir(self.defaults.data())
+                .unwrap_or_else(|| config.scan_download_dir_path());
+            match start_bbdown_serve(&config.bbdown_launch_args, Some(work_dir.as_path())) {
+                Ok(process) => {
+                    println!("{}", style("已启动 bbdown serve 子进程。").green());
+                    self.serve_pool.insert(serve_url.clone(), process);
+                    launched = true;
                 }
-                println!(
-                    "{}",
-                    style("未执行实际下载操作，目录状态保持不变。").yellow()
-                );
-            } else {
-                let mut serve_process = None;
-                if config.bbdown_auto_launch {
-                    let work_dir = config
-                        .resolve_work_dir(self.defaults.data())
-                        .unwrap_or_else(|| config.scan_download_dir_path());
-                    match start_bbdown_serve(&config.bbdown_launch_args, Some(work_dir.as_path())) {
-                        Ok(process) => {
-                            println!("{}", style("已启动 bbdown serve 子进程。").green());
-                            serve_process = Some(process);
-                        }
-                        Err(err) => {
-                            println!(
-                                "{}",
-                                style(format!(
-                                    "自动启动 bbdown serve 失败：{err}。将尝试连接已有服务。"
-                                ))
-                                .yellow()
-                            );
-                        }
-                    }
-                } else {
+                Err(err) => {
                     println!(
                         "{}",
-                        style(format!("使用外部 bbdown serve 服务：{}", serve_url)).cyan()
+                        style(format!(
+                            "自动启动 bbdown serve 失败：{err}。将尝试连接已有服务。"
+                        ))
+                        .yellow()
                     );
                 }
+            }
+        } else {
+            println!(
+                "{}",
+                style(format!("使用外部 bbdown serve 服务：{}", serve_url)).cyan()
+            );
+        }
 
-                let api = BbdownApiClient::new(&serve_url, Duration::from_secs(30))?;
-                let max_concurrency = config.max_concurrency(self.defaults.data());
-                let poll_interval = config.poll_interval();
-                let wait_timeout = Duration::from_secs(config.timeout_secs.max(600));
-                let mut pending_queue: VecDeque<String> = missing_bvids.iter().cloned().collect();
-                let target_keys: HashSet<String> = missing_bvids
-                    .iter()
-                    .map(|value| normalize_target_key(value))
-                    .collect();
-                let mut submitted_total = 0usize;
-                let assign_spinner = ProgressBar::new_spinner();
-                assign_spinner.set_style(
-                    ProgressStyle::with_template("{spinner:.green} {msg}")
-                        .unwrap_or_else(|_| ProgressStyle::default_spinner()),
-                );
-                assign_spinner.enable_steady_tick(Duration::from_millis(120));
-                while !pending_queue.is_empty() {
-                    let running = api.get_running()?;
-                    let active_mine = running
-                        .iter()
-                        .filter_map(|task| task.target_key())
-                        .filter(|key| target_keys.contains(key))
-                        .count() as u32;
-
-                    if active_mine >= max_concurrency {
-                        assign_spinner.set_message(format!(
-                            "达到并发上限 {}，当前运行 {} 个任务，待提交 {} 个",
-                            max_concurrency,
-                            active_mine,
-                            pending_queue.len()
-                        ));
-                        thread::sleep(poll_interval);
-                        continue;
-                    }
-
-                    let available_slots = (max_concurrency - active_mine) as usize;
-                    let mut batch_submitted = 0usize;
-                    for _ in 0..available_slots {
-                        if let Some(bvid) = pending_queue.pop_front() {
-                            api.add_task(
-                                &bvid,
-                                file_pattern.as_deref(),
-                                multi_file_pattern.as_deref(),
-                            )
-                            .with_context(|| format!("提交下载任务 {bvid} 失败"))?;
-                            batch_submitted += 1;
-                            submitted_total += 1;
-                            assign_spinner.set_message(format!(
-                                "已提交 {}/{}，当前运行 {} 个任务，队列剩余 {} 个",
-                                submitted_total,
-                                missing_bvids.len(),
-                                active_mine + batch_submitted as u32,
-                                pending_queue.len()
-                            ));
-                        } else {
-                            break;
-                        }
-                    }
+        let scope = self.interrupt.begin();
+        let outcome = self.download_missing(
+            &config,
+            &missing_bvids,
+            &serve_url,
+            file_pattern.as_deref(),
+            multi_file_pattern.as_deref(),
+            &scope.token(),
+        );
+        if launched && !self.keep_serve {
+            println!("正在关闭 bbdown serve...");
+            if let Err(err) = self.serve_pool.stop(&serve_url) {
+                println!("关闭 bbdown serve 失败: {err}");
+            }
+        }
+        if let Err(err) = outcome {
+            if !scope.is_cancelled() {
+                return Err(err);
+            }
+            println!(
+                "{}",
+                style("下载已中断，下次检查缺漏时会重新提交未完成的条目。").yellow()
+            );
+            return Ok(MissingOutcome::Interrupted);
+        }
 
-                    if batch_submitted == 0 {
-                        thread::sleep(poll_interval);
-                    } else {
-                        thread::sleep(Duration::from_millis(200));
-                    }
-                }
-                assign_spinner.finish_with_message("全部待提交任务已推送至 bbdown serve");
+        println!("正在重新扫描目录以确认缺漏情况...");
+        let refreshed_mapping = match scan_directory_bvids(&download_dir) {
+            Ok(data) => data,
+            Err(err) => {
+                println!("重新扫描下载目录失败: {err}");
+                return Ok(MissingOutcome::Failed);
+            }
+        };
+        self.library
+            .record_local_files(&download_dir, &refreshed_mapping)?;
+        let refreshed_missing: Vec<LibraryVideo> = self
+            .library
+            .folder_videos(media_id)?
+            .into_iter()
+            .filter(LibraryVideo::is_missing)
+            .collect();
+        if refreshed_missing.is_empty() {
+            println!("{}", style("缺漏已全部补齐。").green());
+            Ok(MissingOutcome::Complete)
+        } else {
+            println!(
+                "{}",
+                style(format!("仍有 {} 个条目缺失：", refreshed_missing.len())).yellow()
+            );
+            let remaining = refreshed_missing.len();
+            for video in refreshed_missing {
+                println!("• {}", style(video.bv_id).yellow());
+            }
+            Ok(MissingOutcome::Remaining(remaining))
+        }
+    }
 
-                let progress_bar = ProgressBar::new_spinner();
-                progress_bar.set_style(
-                    ProgressStyle::with_template("{spinner:.green} {msg}")
-                        .unwrap_or_else(|_| ProgressStyle::default_spinner()),
-                );
-                progress_bar.enable_steady_tick(Duration::from_millis(120));
-                let spinner = progress_bar.clone();
-                api.wait_until_idle(
-                    config.poll_interval(),
-                    wait_timeout,
-                    &missing_bvids,
-                    move |running, pending| {
-                        if let Some(task) = running.first() {
-                            let title = task.title.as_deref().unwrap_or("未命名任务");
-                            spinner.set_message(format!(
-                                "等待下载完成，运行中 {} 个，剩余待确认 {} 个（{}）",
-                                running.len(),
-                                pending,
-                                title
-                            ));
-                        } else {
-                            spinner.set_message(format!(
-                                "等待下载任务完成，剩余待确认 {} 个目标",
-                                pending
-                            ));
-                        }
-                    },
-                )?;
-                progress_bar.finish_with_message("全部任务已完成");
+    /// 关闭守护模式保留的全部 bbdown serve。
+    fn stop_kept_serves(&mut self) {
+        self.keep_serve = false;
+        for (serve_url, result) in self.serve_pool.stop_all() {
+            println!("已关闭 bbdown serve（{serve_url}）");
+            if let Err(err) = result {
+                println!("关闭 bbdown serve 失败: {err}");
+            }
+        }
+    }
 
-                if let Err(err) = api.remove_finished() {
-                    println!("移除已完成任务时出现问题：{err}");
-                }
-                drop(serve_process);
+    /// 按并发上限向 bbdown serve 提交缺失条目并等待完成，`cancel` 触发后尽快返回错误。
+    fn download_missing(
+        &self,
+        config: &FavConfig,
+        missing_bvids: &[String],
+        serve_url: &str,
+        file_pattern: Option<&str>,
+        multi_file_pattern: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<()> {
+        let api = BbdownApiClient::new(
+            serve_url,
+            Duration::from_secs(30),
+            config.bbdown_proxy_config(self.defaults.data()).as_ref(),
+        )?;
+        let max_concurrency = config.max_concurrency(self.defaults.data());
+        let poll_interval = config.poll_interval();
+        let wait_timeout = Duration::from_secs(config.timeout_secs.max(600));
+        let mut pending_queue: VecDeque<String> = missing_bvids.iter().cloned().collect();
+        let target_keys: HashSet<String> = missing_bvids
+            .iter()
+            .map(|value| normalize_target_key(value))
+            .collect();
+        let mut submitted_total = 0usize;
+        let assign_spinner = ProgressBar::new_spinner();
+        assign_spinner.set_style(
+            ProgressStyle::with_template("{spinner:.green} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        assign_spinner.enable_steady_tick(Duration::from_millis(120));
+        while !pending_queue.is_empty() {
+            let running = api.get_running()?;
+            let active_mine = running
+                .iter()
+                .filter_map(|task| task.target_key())
+                .filter(|key| target_keys.contains(key))
+                .count() as u32;
+
+            if active_mine >= max_concurrency {
+                assign_spinner.set_message(format!(
+                    "达到并发上限 {}，当前运行 {} 个任务，待提交 {} 个",
+                    max_concurrency,
+                    active_mine,
+                    pending_queue.len()
+                ));
+                sleep_unless_cancelled(cancel, poll_interval)?;
+                continue;
+            }
 
-                println!("正在重新扫描目录以确认缺漏情况...");
-                let refreshed_mapping = match scan_directory_bvids(&download_dir) {
-                    Ok(data) => data,
-                    Err(err) => {
-                        println!("重新扫描下载目录失败: {err}");
-                        pause_with_message("按回车返回...")?;
-                        terminal::enable_raw_mode().ok();
-                        return Ok(());
-                    }
-                };
-                let refreshed_existing: Vec<String> = refreshed_mapping.keys().cloned().collect();
-                let refreshed_missing = find_missing_videos(&csv_rows, &refreshed_existing);
-                if refreshed_missing.is_empty() {
-                    println!("{}", style("缺漏已全部补齐。").green());
+            let available_slots = (max_concurrency - active_mine) as usize;
+            let mut batch_submitted = 0usize;
+            for _ in 0..available_slots {
+                if let Some(bvid) = pending_queue.pop_front() {
+                    api.add_task(&bvid, file_pattern, multi_file_pattern)
+                        .with_context(|| format!("提交下载任务 {bvid} 失败"))?;
+                    batch_submitted += 1;
+                    submitted_total += 1;
+                    assign_spinner.set_message(format!(
+                        "已提交 {}/{}，当前运行 {} 个任务，队列剩余 {} 个",
+                        submitted_total,
+                        missing_bvids.len(),
+                        active_mine + batch_submitted as u32,
+                        pending_queue.len()
+                    ));
                 } else {
-                    println!(
-                        "{}",
-                        style(format!("仍有 {} 个条目缺失：", refreshed_missing.len())).yellow()
-                    );
-                    for row in refreshed_missing {
-                        if let Some(bvid) = extract_bvid(&row) {
-                            println!("• {}", style(bvid).yellow());
-                        }
-                    }
+                    break;
                 }
             }
+
+            if batch_submitted == 0 {
+                sleep_unless_cancelled(cancel, poll_interval)?;
+            } else {
+                sleep_unless_cancelled(cancel, Duration::from_millis(200))?;
+            }
         }
+        assign_spinner.finish_with_message("全部待提交任务已推送至 bbdown serve");
 
-        pause_with_message("按回车返回...")?;
-        terminal::enable_raw_mode().ok();
+        let progress_bar = ProgressBar::new_spinner();
+        progress_bar.set_style(
+            ProgressStyle::with_template("{spinner:.green} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        progress_bar.enable_steady_tick(Duration::from_millis(120));
+        let spinner = progress_bar.clone();
+        api.wait_until_idle(
+            config.poll_interval(),
+            wait_timeout,
+            missing_bvids,
+            cancel,
+            move |running, pending| {
+                if let Some(task) = running.first() {
+                    let title = task.title.as_deref().unwrap_or("未命名任务");
+                    spinner.set_message(format!(
+                        "等待下载完成，运行中 {} 个，剩余待确认 {} 个（{}）",
+                        running.len(),
+                        pending,
+                        title
+                    ));
+                } else {
+                    spinner.set_message(format!("等待下载任务完成，剩余待确认 {} 个目标", pending));
+                }
+            },
+        )?;
+        progress_bar.finish_with_message("全部任务已完成");
+
+        if let Err(err) = api.remove_finished() {
+            println!("移除已完成任务时出现问题：{err}");
+        }
         Ok(())
     }
 }
 
-fn extract_bvid(row: &CsvRow) -> Option<String> {
-    for key in ["bv_id", "BV号", "视频BV号"] {
-        if let Some(value) = row.get(key) {
-            let trimmed = value.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
+/// 纯数字 ID、ml号与短链接统一改写为规范链接再保存，网页链接原样保留。
+fn canonical_fav_url(input: &str, proxy: Option<ProxyConfig>) -> Result<(String, FavSource)> {
+    let input = input.trim();
+    match parse_source_ref(input)? {
+        SourceRef::Url(source) => Ok((input.to_string(), source)),
+        SourceRef::MediaId(media_id) => {
+            let source = FavSource::Folder { media_id };
+            Ok((source.canonical_url(), source))
+        }
+        SourceRef::ShortLink(_) => {
+            let options = ExportOptions {
+                fav_url: input.to_string(),
+                proxy,
+                ..Default::default()
+            };
+            let source = resolve_source_blocking(&options)
+                .with_context(|| format!("短链接解析失败: {input}"))?;
+            println!("短链接指向{}: {}", source.kind(), source.canonical_url());
+            Ok((source.canonical_url(), source))
         }
     }
-    None
+}
+
+/// 留空保留原值、`-` 清除；地址无效时提示并保留原值。
+fn parse_proxy_input(input: &str, current: Option<String>) -> Option<String> {
+    match input.trim() {
+        "" => current,
+        "-" => None,
+        other => match ProxyConfig::new(other).to_proxy() {
+            Ok(_) => Some(other.to_string()),
+            Err(err) => {
+                println!("{}", style(format!("{err}，已保留原设置。")).yellow());
+                current
+            }
+        },
+    }
 }
 
 fn normalize_optional(input: String) -> Option<String> {
@@ -948,17 +1794,312 @@ fn parse_concurrency_input(input: &str, current: Option<u32>) -> Option<u32> {
     }
 }
 
+/// 检查更新在导出前后需要共享的状态。
+struct PendingUpdate {
+    index: usize,
+    config: FavConfig,
+    media_id: i64,
+    old_csv_path: PathBuf,
+    old_rows: Vec<CsvRow>,
+    backup_path: PathBuf,
+    had_old_file: bool,
+    new_csv_path: PathBuf,
+    full_scan: bool,
+}
+
+impl PendingUpdate {
+    /// 放弃本次更新：删除未完成的新 CSV，并把备份还原为旧 CSV。
+    fn rollback(&self) -> Result<()> {
+        if self.new_csv_path.exists() {
+            fs::remove_file(&self.new_csv_path).with_context(|| {
+                format!("删除未完成的 CSV 失败: {}", self.new_csv_path.display())
+            })?;
+        }
+        if self.had_old_file {
+            fs::rename(&self.backup_path, &self.old_csv_path).with_context(|| "恢复旧 CSV 失败")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct SyncSummary {
+    succeeded: usize,
+    failed: usize,
+    interrupted: bool,
+}
+
+/// 检查缺漏的结果，子命令据此决定退出码。
+#[derive(Debug, PartialEq, Eq)]
+enum MissingOutcome {
+    Complete,
+    /// 仍缺失的条目数（dry-run 时为待下载数）。
+    Remaining(usize),
+    Interrupted,
+    Failed,
+}
+
 enum MainAction {
     NewConfig,
     UseConfig,
+    SyncAll,
+    Login,
     EditDefaults,
     Exit,
 }
 
-fn main() -> Result<()> {
+/// 决定配置能否共用同一个客户端的设置。
+#[derive(PartialEq)]
+struct ClientKey {
+    cookie: Option<String>,
+    cookie_file: Option<PathBuf>,
+    proxy: Option<ProxyConfig>,
+    base_url: Option<String>,
+    timeout_secs: u64,
+}
+
+impl ClientKey {
+    fn of(options: &ExportOptions) -> Self {
+        Self {
+            cookie: options.cookie.clone(),
+            cookie_file: options.cookie_file.clone(),
+            proxy: options.proxy.clone(),
+            base_url: options.base_url.clone(),
+            timeout_secs: options.timeout_secs,
+        }
+    }
+}
+
+fn is_cancelled(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ExportError>()
+        .is_some_and(ExportError::is_cancelled)
+}
+
+/// 把文件移入下载目录下的 `REMOVED_ARCHIVE_DIR`，保留相对下载目录的路径；
+/// 目标已存在时追加 `-2`、`-3` 等序号，不覆盖已归档的文件。
+fn archive_removed_files(download_dir: &Path, files: &[PathBuf]) -> Result<()> {
+    let archive_dir = download_dir.join(REMOVED_ARCHIVE_DIR);
+    for path in files {
+        let relative = match path.strip_prefix(download_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => match path.file_name() {
+                Some(name) => PathBuf::from(name),
+                None => continue,
+            },
+        };
+        let target = unused_archive_path(&archive_dir.join(relative));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("创建归档目录失败: {}", parent.display()))?;
+        }
+        fs::rename(path, &target).with_context(|| format!("归档失败: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn unused_archive_path(target: &Path) -> PathBuf {
+    if !target.exists() {
+        return target.to_path_buf();
+    }
+    let stem = target
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = target
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (2u32..)
+        .map(|n| target.with_file_name(format!("{stem}-{n}{extension}")))
+        .find(|candidate| !candidate.exists())
+        .expect("归档序号不会耗尽")
+}
+
+fn config_label(config: &FavConfig, index: usize) -> String {
+    config
+        .name
+        .as_ref()
+        .filter(|name| !name.is_empty())
+        .cloned()
+        .unwrap_or_else(|| format!("收藏夹 {}", index + 1))
+}
+
+fn export_spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("{spinner:.green} {prefix}：{msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+}
+
+/// 按导出事件更新进度条：得知条目总数后切换为带计数的进度条。
+fn apply_export_event(bar: &ProgressBar, event: &ExportEvent) {
+    match event {
+        ExportEvent::FolderInfo {
+            total: Some(total), ..
+        } => {
+            bar.set_style(
+                ProgressStyle::with_template(
+                    "{spinner:.green} {prefix} [{bar:30.cyan/blue}] {pos}/{len} {msg}",
+                )
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+            );
+            bar.set_length(*total);
+        }
+        ExportEvent::PageFetched { processed, .. } => bar.set_position(*processed),
+        _ => {}
+    }
+    bar.set_message(event.to_string());
+}
+
+/// 每个进行中的收藏夹各占一行进度，结束后打印结果。
+fn sync_progress_callback() -> SyncCallback {
+    let multi = MultiProgress::new();
+    let bars: Arc<Mutex<HashMap<usize, ProgressBar>>> = Arc::default();
+    Arc::new(move |event: SyncEvent| {
+        let mut bars = bars.lock().unwrap_or_else(|err| err.into_inner());
+        match event {
+            SyncEvent::Started { index, name } => {
+                let bar = multi.add(ProgressBar::new_spinner());
+                bar.set_style(export_spinner_style());
+                bar.set_prefix(name);
+                bar.set_message("开始抓取");
+                bar.enable_steady_tick(Duration::from_millis(120));
+                bars.insert(index, bar);
+            }
+            SyncEvent::Progress { index, event } => {
+                if let Some(bar) = bars.get(&index) {
+                    apply_export_event(bar, &event);
+                }
+            }
+            SyncEvent::Finished { index, name, error } => {
+                if let Some(bar) = bars.remove(&index) {
+                    bar.finish_and_clear();
+                }
+                let line = match error {
+                    None => style(format!("✓ {name}")).green().to_string(),
+                    Some(err) => style(format!("✗ {err}")).red().to_string(),
+                };
+                multi.println(line).ok();
+            }
+        }
+    })
+}
+
+/// 启动时检查扫码登录凭据，临近过期或服务端要求时自动刷新；失败只提示不中断。
+fn refresh_login_credentials(path: &Path, proxy: Option<ProxyConfig>) {
+    if !path.exists() {
+        return;
+    }
+    let options = LoginOptions {
+        proxy,
+        ..Default::default()
+    };
+    match refresh_credentials_file_blocking(path, options) {
+        Ok(true) => println!("{}", style("登录凭据已自动刷新。").green()),
+        Ok(false) => {}
+        Err(err) => println!(
+            "{}",
+            style(format!("刷新登录凭据失败: {err}，必要时请重新扫码登录。")).yellow()
+        ),
+    }
+}
+
+fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
     let store = ConfigStore::load(cli.config_path.clone())?;
     let defaults = GlobalDefaultsStore::load(&store.config_dir())?;
-    let mut app = App::new(store, defaults, cli.dry_run);
-    app.run()
+    let library_path = store.library_path();
+    let library = Library::open(&library_path)
+        .with_context(|| format!("打开本地视频库失败: {}", library_path.display()))?;
+    refresh_login_credentials(&store.credentials_path(), defaults.data().proxy_config());
+    let interrupt = Interrupt::install()?;
+    let interactive = cli.command.is_none();
+    let mut app = App::new(
+        store,
+        defaults,
+        library,
+        interrupt,
+        cli.dry_run,
+        interactive,
+    );
+    match cli.command {
+        Some(command) => app.run_command(command),
+        None => {
+            app.run()?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use favlist_core::VideoEntry;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    const BVID: &str = "BV1L9Uoa9EUx";
+
+    #[test]
+    fn archived_files_are_not_counted_as_downloaded() -> Result<()> {
+        let dir = tempdir()?;
+        let download_dir = dir.path().join("downloads");
+        let first = download_dir.join("a").join(format!("{BVID}.mp4"));
+        let second = download_dir.join("b").join(format!("{BVID}.mp4"));
+        for path in [&first, &second] {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, path.to_string_lossy().as_bytes())?;
+        }
+
+        let mut library = Library::open(&dir.path().join("library.db"))?;
+        let entry = VideoEntry {
+            bv_id: BVID.to_string(),
+            title: "已取消收藏".to_string(),
+            ..Default::default()
+        };
+        library.record_folder(1, "收藏夹", &[entry], "2026-01-01T00-00-00")?;
+        let mapping = scan_directory_bvids(&download_dir)?;
+        library.record_local_files(&download_dir, &mapping)?;
+        assert!(!library.video(BVID)?.unwrap().is_missing());
+
+        archive_removed_files(&download_dir, &mapping[BVID])?;
+        let archive_dir = download_dir.join(REMOVED_ARCHIVE_DIR);
+        // 同名文件按原所在子目录归档，互不覆盖。
+        assert_eq!(
+            fs::read_to_string(archive_dir.join("a").join(format!("{BVID}.mp4")))?,
+            first.to_string_lossy()
+        );
+        assert_eq!(
+            fs::read_to_string(archive_dir.join("b").join(format!("{BVID}.mp4")))?,
+            second.to_string_lossy()
+        );
+
+        let refreshed = scan_directory_bvids(&download_dir)?;
+        assert!(refreshed.is_empty());
+        library.record_local_files(&download_dir, &refreshed)?;
+        let video = library.video(BVID)?.unwrap();
+        assert!(video.is_missing());
+        assert_eq!(video.download_state, DownloadState::Pending);
+        Ok(())
+    }
+
+    #[test]
+    fn archiving_twice_keeps_both_copies() -> Result<()> {
+        let dir = tempdir()?;
+        let download_dir = dir.path();
+        let path = download_dir.join(format!("{BVID}.mp4"));
+        for content in ["旧", "新"] {
+            fs::write(&path, content)?;
+            archive_removed_files(download_dir, std::slice::from_ref(&path))?;
+        }
+        let archive_dir = download_dir.join(REMOVED_ARCHIVE_DIR);
+        assert_eq!(
+            fs::read_to_string(archive_dir.join(format!("{BVID}.mp4")))?,
+            "旧"
+        );
+        assert_eq!(
+            fs::read_to_string(archive_dir.join(format!("{BVID}-2.mp4")))?,
+            "新"
+        );
+        Ok(())
+    }
 }
@@ -0,0 +1,125 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Local};
+use croner::Cron;
+
+/// 间隔过短会频繁请求B站，守护模式下不接受短于该值的间隔。
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 配置的同步计划：固定间隔（`30m`、`6h`、`1day` 等），
+/// 或按本地时间匹配的 5 段 cron 表达式（`0 3 * * *`，也支持 `@daily` 等别名）。
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Interval(Duration),
+    Cron(Box<Cron>),
+}
+
+impl Schedule {
+    /// 计算 `after` 之后的下一次运行时间。
+    pub fn next_after(&self, after: DateTime<Local>) -> Result<DateTime<Local>> {
+        match self {
+            Schedule::Interval(interval) => Ok(after + chrono::Duration::from_std(*interval)?),
+            Schedule::Cron(cron) => cron
+                .find_next_occurrence(&after, false)
+                .map_err(|err| anyhow!("计算下次运行时间失败: {err}")),
+        }
+    }
+
+    /// 首次运行时间：间隔计划立即运行，cron 计划等到下一个匹配时刻。
+    pub fn first_run(&self, now: DateTime<Local>) -> Result<DateTime<Local>> {
+        match self {
+            Schedule::Interval(_) => Ok(now),
+            Schedule::Cron(_) => self.next_after(now),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.starts_with('@') || input.split_whitespace().count() > 1 {
+            let cron = Cron::new(input)
+                .parse()
+                .map_err(|err| anyhow!("无效的 cron 表达式 `{input}`: {err}"))?;
+            return Ok(Schedule::Cron(Box::new(cron)));
+        }
+        let interval = humantime::parse_duration(input)
+            .map_err(|err| anyhow!("无效的同步间隔 `{input}`: {err}"))?;
+        if interval < MIN_INTERVAL {
+            bail!("同步间隔不能短于 1 分钟，收到 `{input}`");
+        }
+        Ok(Schedule::Interval(interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .single()
+            .expect("本地时间应唯一")
+    }
+
+    #[test]
+    fn parses_intervals() {
+        let schedule: Schedule = "6h".parse().unwrap();
+        assert!(
+            matches!(schedule, Schedule::Interval(interval) if interval == Duration::from_secs(6 * 3600))
+        );
+        let schedule: Schedule = " 1m ".parse().unwrap();
+        assert!(matches!(schedule, Schedule::Interval(interval) if interval == MIN_INTERVAL));
+    }
+
+    #[test]
+    fn rejects_short_or_invalid_intervals() {
+        let err = "59s".parse::<Schedule>().unwrap_err().to_string();
+        assert!(err.contains("不能短于 1 分钟"), "{err}");
+        let err = "soon".parse::<Schedule>().unwrap_err().to_string();
+        assert!(err.contains("无效的同步间隔"), "{err}");
+    }
+
+    #[test]
+    fn parses_cron_expressions() {
+        assert!(matches!(
+            "0 3 * * *".parse::<Schedule>().unwrap(),
+            Schedule::Cron(_)
+        ));
+        assert!(matches!(
+            "@daily".parse::<Schedule>().unwrap(),
+            Schedule::Cron(_)
+        ));
+        let err = "0 25 * * *".parse::<Schedule>().unwrap_err().to_string();
+        assert!(err.contains("无效的 cron 表达式"), "{err}");
+        assert!("* * *".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn interval_runs_immediately_then_repeats() {
+        let schedule: Schedule = "90m".parse().unwrap();
+        let now = local(2026, 1, 1, 10, 0);
+        assert_eq!(schedule.first_run(now).unwrap(), now);
+        assert_eq!(schedule.next_after(now).unwrap(), local(2026, 1, 1, 11, 30));
+    }
+
+    #[test]
+    fn cron_waits_for_next_match() {
+        let schedule: Schedule = "0 3 * * *".parse().unwrap();
+        let now = local(2026, 1, 1, 10, 0);
+        assert_eq!(schedule.first_run(now).unwrap(), local(2026, 1, 2, 3, 0));
+        // 恰好落在匹配时刻时取下一次，避免同一时刻重复运行。
+        let at_match = local(2026, 1, 2, 3, 0);
+        assert_eq!(
+            schedule.next_after(at_match).unwrap(),
+            local(2026, 1, 3, 3, 0)
+        );
+    }
+}
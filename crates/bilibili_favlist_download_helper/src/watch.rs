@@ -0,0 +1,469 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitCode};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, SecondsFormat};
+use clap::Args;
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{find_config, usage_error, RemovedAction};
+use crate::config::FavConfig;
+use crate::interrupt::{sleep_unless_cancelled, INTERRUPTED_EXIT_CODE};
+use crate::schedule::Schedule;
+use crate::{config_label, App, MissingOutcome};
+
+/// 空闲时最长睡眠该时长后重新核对时间，避免系统休眠或调整时钟后错过计划。
+const MAX_IDLE_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// 配置序号（见 list，从 1 开始）或名称，可指定多个
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    targets: Vec<String>,
+
+    /// 处理全部配置
+    #[arg(long)]
+    all: bool,
+
+    /// 未设置同步计划的配置使用的计划：间隔（如 6h）或 cron 表达式
+    #[arg(long)]
+    default_schedule: Option<String>,
+
+    /// 状态文件路径，默认为配置目录下的 watch-status.json
+    #[arg(long)]
+    status_file: Option<PathBuf>,
+
+    /// 已取消收藏视频的本地文件处理方式
+    #[arg(long, value_enum, default_value_t = RemovedAction::Keep)]
+    removed: RemovedAction,
+}
+
+/// 写入状态文件的整体状态，供外部脚本或监控读取。
+#[derive(Serialize, Deserialize, Debug)]
+struct WatchStatus {
+    pid: u32,
+    started_at: String,
+    updated_at: String,
+    /// 正在处理的配置名称，空闲时为空
+    running: Option<String>,
+    /// 当前保留的 bbdown serve 地址
+    serve_urls: Vec<String>,
+    configs: Vec<ConfigStatus>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ConfigStatus {
+    index: usize,
+    name: String,
+    fav_url: String,
+    schedule: String,
+    last_started_at: Option<String>,
+    last_finished_at: Option<String>,
+    /// `ok`、`incomplete`、`failed` 或 `interrupted`
+    last_result: Option<String>,
+    /// 检查更新是否成功
+    sync_ok: Option<bool>,
+    /// 检查缺漏后仍缺失的条目数
+    missing: Option<usize>,
+    error: Option<String>,
+    next_run: String,
+}
+
+struct WatchEntry {
+    index: usize,
+    schedule: Schedule,
+    next_run: DateTime<Local>,
+    status: ConfigStatus,
+}
+
+impl App {
+    /// 守护模式：到达各配置的计划时间后依次检查更新与缺漏，期间保留 bbdown serve。
+    /// 守护只会被 Ctrl+C 结束，无论空闲还是运行中，退出码都与其他子命令被中断时一致（130）。
+    pub(crate) fn watch(&mut self, args: WatchArgs) -> Result<ExitCode> {
+        let status_path = args
+            .status_file
+            .clone()
+            .unwrap_or_else(|| self.store.watch_status_path());
+        let mut entries = match self.watch_entries(&args, &status_path) {
+            Ok(entries) => entries,
+            Err(err) => return Ok(usage_error(err)),
+        };
+        if entries.is_empty() {
+            println!("当前没有任何已保存的配置。");
+            return Ok(ExitCode::SUCCESS);
+        }
+        println!(
+            "守护模式已启动，监控 {} 个收藏夹，状态写入 {}（Ctrl+C 退出）",
+            entries.len(),
+            status_path.display()
+        );
+
+        self.removed_action = Some(args.removed);
+        self.keep_serve = true;
+        let mut status = WatchStatus {
+            pid: process::id(),
+            started_at: format_time(Local::now()),
+            updated_at: String::new(),
+            running: None,
+            serve_urls: Vec::new(),
+            configs: Vec::new(),
+        };
+        let outcome = self.watch_loop(&mut entries, &mut status, &status_path);
+        self.stop_kept_serves();
+        status.running = None;
+        status.serve_urls.clear();
+        if let Err(err) = write_status(&status_path, &mut status, &entries) {
+            println!("{}", style(format!("写入状态文件失败: {err:#}")).yellow());
+        }
+        outcome
+    }
+
+    /// 解析目标配置的计划，沿用状态文件中计划未变的下次运行时间。
+    fn watch_entries(&self, args: &WatchArgs, status_path: &Path) -> Result<Vec<WatchEntry>> {
+        let configs = self.store.configs();
+        let mut indices = Vec::new();
+        if args.all {
+            indices.extend(0..configs.len());
+        } else {
+            for target in &args.targets {
+                let index = find_config(configs, target)?;
+                if !indices.contains(&index) {
+                    indices.push(index);
+                }
+            }
+        }
+        let previous = read_status(status_path);
+        let now = Local::now();
+        indices
+            .into_iter()
+            .map(|index| {
+                watch_entry(
+                    index,
+                    &configs[index],
+                    args.default_schedule.as_deref(),
+                    &previous,
+                    now,
+                )
+            })
+            .collect()
+    }
+
+    fn watch_loop(
+        &mut self,
+        entries: &mut [WatchEntry],
+        status: &mut WatchStatus,
+        status_path: &Path,
+    ) -> Result<ExitCode> {
+        // 外层作用域覆盖空闲等待与两次操作之间，Ctrl+C 时结束守护并关闭 bbdown serve。
+        let scope = self.interrupt.begin();
+        let mut announced = None;
+        loop {
+            let now = Local::now();
+            let due = entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.next_run <= now)
+                .min_by_key(|(_, entry)| entry.next_run)
+                .map(|(position, _)| position);
+            let Some(position) = due else {
+                let next = entries
+                    .iter()
+                    .min_by_key(|entry| entry.next_run)
+                    .expect("守护模式至少包含一个配置");
+                if announced != Some(next.next_run) {
+                    announced = Some(next.next_run);
+                    status.running = None;
+                    self.save_status(status_path, status, entries);
+                    println!(
+                        "下次运行：「{}」于 {}",
+                        next.status.name,
+                        next.next_run.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+                let wait = (next.next_run - now).to_std().unwrap_or_default();
+                if sleep_unless_cancelled(&scope.token(), wait.min(MAX_IDLE_SLEEP)).is_err() {
+                    println!("{}", style("已退出守护模式。").yellow());
+                    return Ok(ExitCode::from(INTERRUPTED_EXIT_CODE));
+                }
+                continue;
+            };
+
+            announced = None;
+            let entry = &mut entries[position];
+            let index = entry.index;
+            let label = entry.status.name.clone();
+            entry.status.last_started_at = Some(format_time(Local::now()));
+            status.running = Some(label.clone());
+            self.save_status(status_path, status, entries);
+
+            println!("{}", style(format!("== {label} ==")).cyan());
+            let interrupted = self.run_scheduled(index, &mut entries[position].status);
+            let entry = &mut entries[position];
+            let finished = Local::now();
+            entry.status.last_finished_at = Some(format_time(finished));
+            entry.next_run = entry.schedule.next_after(finished)?;
+            entry.status.next_run = format_time(entry.next_run);
+            if interrupted || scope.is_cancelled() {
+                println!("{}", style("已退出守护模式。").yellow());
+                return Ok(ExitCode::from(INTERRUPTED_EXIT_CODE));
+            }
+        }
+    }
+
+    /// 对单个配置检查更新与缺漏并记录结果，返回是否被 Ctrl+C 中断。
+    fn run_scheduled(&mut self, index: usize, status: &mut ConfigStatus) -> bool {
+        status.sync_ok = None;
+        status.missing = None;
+        status.error = None;
+        let summary = match self.sync_configs(vec![index]) {
+            Ok(summary) => summary,
+            Err(err) => {
+                println!("{}", style(format!("检查更新失败: {err:#}")).red());
+                status.last_result = Some("failed".to_string());
+                status.error = Some(format!("{err:#}"));
+                return false;
+            }
+        };
+        if summary.interrupted {
+            status.last_result = Some("interrupted".to_string());
+            return true;
+        }
+        status.sync_ok = Some(summary.failed == 0);
+
+        let result = match self.fill_missing(index) {
+            Ok(MissingOutcome::Interrupted) => {
+                status.last_result = Some("interrupted".to_string());
+                return true;
+            }
+            Ok(MissingOutcome::Complete) if summary.failed == 0 => {
+                status.missing = Some(0);
+                "ok"
+            }
+            Ok(MissingOutcome::Complete) => {
+                status.missing = Some(0);
+                "incomplete"
+            }
+            Ok(MissingOutcome::Remaining(remaining)) => {
+                status.missing = Some(remaining);
+                "incomplete"
+            }
+            Ok(MissingOutcome::Failed) => "failed",
+            Err(err) => {
+                println!("{}", style(format!("检查缺漏失败: {err:#}")).red());
+                status.error = Some(format!("{err:#}"));
+                "failed"
+            }
+        };
+        status.last_result = Some(result.to_string());
+        false
+    }
+
+    /// 写入状态文件，失败只提示，不中断守护。
+    fn save_status(&self, path: &Path, status: &mut WatchStatus, entries: &[WatchEntry]) {
+        status.serve_urls = self.serve_pool.serve_urls();
+        if let Err(err) = write_status(path, status, entries) {
+            println!("{}", style(format!("写入状态文件失败: {err:#}")).yellow());
+        }
+    }
+}
+
+/// 生成单个配置的守护条目；状态文件中同一收藏夹且计划未变时沿用其下次运行时间。
+fn watch_entry(
+    index: usize,
+    config: &FavConfig,
+    default_schedule: Option<&str>,
+    previous: &[ConfigStatus],
+    now: DateTime<Local>,
+) -> Result<WatchEntry> {
+    let label = config_label(config, index);
+    let Some(raw) = config.schedule.as_deref().or(default_schedule) else {
+        bail!("「{label}」未设置同步计划，可用 edit --schedule 设置或传入 --default-schedule");
+    };
+    let raw = raw.trim();
+    let schedule: Schedule = raw
+        .parse()
+        .with_context(|| format!("「{label}」的同步计划无效"))?;
+    let restored = previous
+        .iter()
+        .find(|status| status.fav_url == config.fav_url && status.schedule == raw);
+    let next_run = match restored.and_then(|status| parse_time(&status.next_run)) {
+        Some(next_run) => next_run,
+        None => schedule.first_run(now)?,
+    };
+    let status = ConfigStatus {
+        index: index + 1,
+        name: label,
+        fav_url: config.fav_url.clone(),
+        schedule: raw.to_string(),
+        next_run: format_time(next_run),
+        ..restored.cloned().unwrap_or_else(ConfigStatus::empty)
+    };
+    Ok(WatchEntry {
+        index,
+        schedule,
+        next_run,
+        status,
+    })
+}
+
+impl ConfigStatus {
+    fn empty() -> Self {
+        Self {
+            index: 0,
+            name: String::new(),
+            fav_url: String::new(),
+            schedule: String::new(),
+            last_started_at: None,
+            last_finished_at: None,
+            last_result: None,
+            sync_ok: None,
+            missing: None,
+            error: None,
+            next_run: String::new(),
+        }
+    }
+}
+
+/// 先写临时文件再重命名，读取方不会看到写了一半的内容。
+fn write_status(path: &Path, status: &mut WatchStatus, entries: &[WatchEntry]) -> Result<()> {
+    status.updated_at = format_time(Local::now());
+    status.configs = entries.iter().map(|entry| entry.status.clone()).collect();
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(status)?)
+        .with_context(|| format!("写入失败: {}", temp_path.display()))?;
+    fs::rename(&temp_path, path).with_context(|| format!("写入失败: {}", path.display()))?;
+    Ok(())
+}
+
+/// 读取上次守护留下的状态，文件不存在或无法解析时视为无记录。
+fn read_status(path: &Path) -> Vec<ConfigStatus> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<WatchStatus>(&content).ok())
+        .map(|status| status.configs)
+        .unwrap_or_default()
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.with_timezone(&Local))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    use super::*;
+
+    const FAV_URL: &str = "https://space.bilibili.com/1/favlist?fid=1001";
+
+    fn sample_config(schedule: Option<&str>) -> FavConfig {
+        serde_json::from_value(json!({
+            "fav_url": FAV_URL,
+            "csv_path": "favlist.csv",
+            "encoding": "utf-8",
+            "page_size": 20,
+            "cookie": null,
+            "timeout_secs": 10,
+            "last_synced_at": null,
+            "name": "稍后看",
+            "schedule": schedule,
+        }))
+        .expect("示例配置应能解析")
+    }
+
+    fn now() -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 1, 1, 10, 0, 0)
+            .single()
+            .expect("本地时间应唯一")
+    }
+
+    fn previous_status(schedule: &str, next_run: DateTime<Local>) -> ConfigStatus {
+        ConfigStatus {
+            index: 1,
+            name: "稍后看".to_string(),
+            fav_url: FAV_URL.to_string(),
+            schedule: schedule.to_string(),
+            last_result: Some("ok".to_string()),
+            missing: Some(0),
+            next_run: format_time(next_run),
+            ..ConfigStatus::empty()
+        }
+    }
+
+    #[test]
+    fn entry_restores_next_run_when_schedule_unchanged() {
+        let saved = now() + chrono::Duration::hours(2);
+        let previous = vec![previous_status("6h", saved)];
+        let entry = watch_entry(0, &sample_config(Some("6h")), None, &previous, now()).unwrap();
+        assert_eq!(entry.next_run, saved);
+        assert_eq!(entry.status.last_result.as_deref(), Some("ok"));
+        assert_eq!(entry.status.missing, Some(0));
+    }
+
+    #[test]
+    fn entry_resets_when_schedule_changed() {
+        let saved = now() + chrono::Duration::hours(2);
+        let previous = vec![previous_status("12h", saved)];
+        let entry = watch_entry(0, &sample_config(Some("6h")), None, &previous, now()).unwrap();
+        // 间隔计划首次立即运行，旧记录不再沿用。
+        assert_eq!(entry.next_run, now());
+        assert_eq!(entry.status.last_result, None);
+        assert_eq!(entry.status.schedule, "6h");
+    }
+
+    #[test]
+    fn entry_uses_default_schedule_or_fails() {
+        let entry = watch_entry(0, &sample_config(None), Some(" 2h "), &[], now()).unwrap();
+        assert_eq!(entry.status.schedule, "2h");
+        assert_eq!(entry.status.index, 1);
+
+        let err = watch_entry(0, &sample_config(None), None, &[], now())
+            .err()
+            .expect("缺少计划时应失败");
+        assert!(err.to_string().contains("未设置同步计划"), "{err}");
+        assert!(watch_entry(0, &sample_config(Some("5s")), None, &[], now()).is_err());
+    }
+
+    #[test]
+    fn status_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("watch-status.json");
+        assert!(read_status(&path).is_empty());
+
+        let entry = watch_entry(0, &sample_config(Some("0 3 * * *")), None, &[], now()).unwrap();
+        let mut status = WatchStatus {
+            pid: 42,
+            started_at: format_time(now()),
+            updated_at: String::new(),
+            running: None,
+            serve_urls: vec!["http://localhost:23333".to_string()],
+            configs: Vec::new(),
+        };
+        write_status(&path, &mut status, std::slice::from_ref(&entry)).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let restored = read_status(&path);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].fav_url, FAV_URL);
+        assert_eq!(restored[0].schedule, "0 3 * * *");
+        assert_eq!(parse_time(&restored[0].next_run), Some(entry.next_run));
+
+        fs::write(&path, "{ 损坏").unwrap();
+        assert!(read_status(&path).is_empty());
+    }
+}
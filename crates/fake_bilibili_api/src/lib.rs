@@ -0,0 +1,5 @@
+pub mod scenario;
+pub mod server;
+
+pub use scenario::{Endpoint, ErrorRule, FolderSpec, InvalidKind, Scenario, VideoSpec};
+pub use server::FakeServer;
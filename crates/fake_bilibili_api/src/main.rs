@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use fake_bilibili_api::{FakeServer, Scenario};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "按场景文件模拟B站收藏夹接口，供本地联调使用", long_about = None)]
+struct Cli {
+    /// 场景文件（.json 或 .yaml/.yml）
+    scenario: PathBuf,
+
+    /// 监听地址
+    #[arg(long = "listen", default_value = "127.0.0.1:23400")]
+    listen: String,
+
+    /// 不打印请求日志
+    #[arg(short = 'q', long = "quiet")]
+    quiet: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let scenario = Scenario::load(&cli.scenario)?;
+    let folder_count = scenario.folders.len();
+    let server = FakeServer::start_with_logging(scenario, &cli.listen, !cli.quiet)?;
+    println!(
+        "模拟接口已启动: {}（{} 个收藏夹），将 base_url 设为该地址即可使用，Ctrl+C 退出",
+        server.base_url(),
+        folder_count
+    );
+    server.wait();
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// 未指定收藏时间时，首个视频使用此时间，之后每条早一分钟，与真实接口的倒序一致。
+const DEFAULT_FAV_TIME: i64 = 1_700_000_000;
+pub const DELETED_TITLE: &str = "已失效视频";
+
+/// 模拟服务器的场景描述，可用 JSON 或 YAML 编写。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    /// 所有请求的默认延迟（毫秒）。
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub folders: Vec<FolderSpec>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderSpec {
+    pub id: i64,
+    #[serde(default)]
+    pub title: String,
+    /// 收藏夹所属用户，`created/list-all` 按此筛选。
+    #[serde(default)]
+    pub mid: i64,
+    /// 覆盖 `folder/info` 返回的条目数，默认为 `videos` 的数量。
+    #[serde(default)]
+    pub media_count: Option<i64>,
+    /// 覆盖全局延迟。
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub videos: Vec<VideoSpec>,
+    #[serde(default)]
+    pub errors: Vec<ErrorRule>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VideoSpec {
+    /// 留空时只返回 `aid`，用于模拟缺少 BV 号的条目。
+    #[serde(default)]
+    pub bvid: String,
+    #[serde(default)]
+    pub aid: Option<i64>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub fav_time: Option<i64>,
+    #[serde(default)]
+    pub upper_mid: i64,
+    #[serde(default)]
+    pub upper_name: String,
+    #[serde(default)]
+    pub duration: i64,
+    #[serde(default)]
+    pub pages: Option<i64>,
+    #[serde(default)]
+    pub invalid: Option<InvalidKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidKind {
+    /// 稿件已删除：`attr` 为 9，标题改为“已失效视频”。
+    Deleted,
+    /// 地区限制等原因不可见：`attr` 为 2，保留原标题。
+    Restricted,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    #[default]
+    Any,
+    Info,
+    List,
+}
+
+/// 按条件注入错误；`times` 为空时一直生效，否则前 `times` 次命中后恢复正常，便于验证重试。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErrorRule {
+    #[serde(default)]
+    pub endpoint: Endpoint,
+    /// 只作用于某一页（仅 `list`）。
+    #[serde(default)]
+    pub page: Option<u32>,
+    /// 业务错误码，如 `-403`、`-352`。
+    #[serde(default)]
+    pub code: Option<i32>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// 以 HTTP 状态码失败（如 412），优先于 `code`。
+    #[serde(default)]
+    pub http_status: Option<u16>,
+    #[serde(default)]
+    pub times: Option<u32>,
+}
+
+impl ErrorRule {
+    pub fn matches(&self, endpoint: Endpoint, page: Option<u32>) -> bool {
+        (self.endpoint == Endpoint::Any || self.endpoint == endpoint)
+            && (self.page.is_none() || self.page == page)
+    }
+}
+
+impl Scenario {
+    /// 按扩展名选择解析方式，`.json` 以外一律按 YAML 解析。
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取场景文件失败: {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::from_json(&content)
+        } else {
+            Self::from_yaml(&content)
+        }
+        .with_context(|| format!("解析场景文件失败: {}", path.display()))
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    pub fn folder(&self, id: i64) -> Option<&FolderSpec> {
+        self.folders.iter().find(|folder| folder.id == id)
+    }
+}
+
+impl FolderSpec {
+    pub fn info_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "fid": self.id / 100,
+            "mid": self.mid,
+            "title": self.title,
+            "media_count": self.media_count.unwrap_or(self.videos.len() as i64),
+        })
+    }
+
+    /// `page` 从 1 开始；越界时返回空列表。
+    pub fn page_json(&self, page: u32, page_size: u32) -> Value {
+        let page_size = page_size.max(1) as usize;
+        let start = (page.max(1) as usize - 1) * page_size;
+        let medias: Vec<Value> = self
+            .videos
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(page_size)
+            .map(|(index, video)| video.media_json(index))
+            .collect();
+        json!({
+            "info": self.info_json(),
+            "medias": medias,
+            "has_more": start + page_size < self.videos.len(),
+        })
+    }
+}
+
+impl VideoSpec {
+    fn media_json(&self, index: usize) -> Value {
+        let (attr, title) = match self.invalid {
+            Some(InvalidKind::Deleted) => (9, DELETED_TITLE),
+            Some(InvalidKind::Restricted) => (2, self.title.as_str()),
+            None => (0, self.title.as_str()),
+        };
+        json!({
+            "id": self.aid.unwrap_or(index as i64 + 1),
+            "type": 2,
+            "title": title,
+            "bvid": self.bvid,
+            "bv_id": self.bvid,
+            "attr": attr,
+            "duration": self.duration,
+            "page": self.pages.unwrap_or(1),
+            "fav_time": self.fav_time.unwrap_or(DEFAULT_FAV_TIME - index as i64 * 60),
+            "upper": {"mid": self.upper_mid, "name": self.upper_name},
+            "cnt_info": {"collect": 0, "play": 0, "danmaku": 0},
+        })
+    }
+}
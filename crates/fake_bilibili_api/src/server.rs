@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use tiny_http::{Header, Request, Response, Server};
+use url::Url;
+
+use crate::scenario::{Endpoint, Scenario};
+
+pub const INFO_PATH: &str = "/x/v3/fav/folder/info";
+pub const LIST_PATH: &str = "/x/v3/fav/resource/list";
+pub const CREATED_LIST_PATH: &str = "/x/v3/fav/folder/created/list-all";
+/// 收藏夹不存在时真实接口返回的错误。
+const NOT_FOUND_CODE: i32 = -404;
+const DEFAULT_PAGE_SIZE: u32 = 20;
+
+/// 在后台线程中运行的模拟服务器，丢弃时停止监听。
+pub struct FakeServer {
+    server: Arc<Server>,
+    addr: SocketAddr,
+    state: Arc<State>,
+    worker: Option<JoinHandle<()>>,
+}
+
+struct State {
+    scenario: Scenario,
+    /// 以（收藏夹, 规则序号）记录错误规则已命中的次数。
+    rule_hits: Mutex<HashMap<(i64, usize), u32>>,
+    requests: AtomicU64,
+    verbose: bool,
+}
+
+impl FakeServer {
+    /// 监听 `addr`（如 `127.0.0.1:0` 由系统分配端口）并立即开始处理请求。
+    pub fn start(scenario: Scenario, addr: &str) -> Result<Self> {
+        Self::start_with_logging(scenario, addr, false)
+    }
+
+    /// `verbose` 为真时每个请求打印一行日志。
+    pub fn start_with_logging(scenario: Scenario, addr: &str, verbose: bool) -> Result<Self> {
+        let server =
+            Arc::new(Server::http(addr).map_err(|err| anyhow!("监听 {addr} 失败: {err}"))?);
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| anyhow!("监听地址不是 TCP 地址"))?;
+        let state = Arc::new(State {
+            scenario,
+            rule_hits: Mutex::new(HashMap::new()),
+            requests: AtomicU64::new(0),
+            verbose,
+        });
+        let worker = {
+            let server = Arc::clone(&server);
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let state = Arc::clone(&state);
+                    thread::spawn(move || state.handle(request));
+                }
+            })
+        };
+        Ok(Self {
+            server,
+            addr,
+            state,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 已收到的请求数（含注入错误的请求）。
+    pub fn request_count(&self) -> u64 {
+        self.state.requests.load(Ordering::SeqCst)
+    }
+
+    /// 阻塞直到监听线程退出。
+    pub fn wait(mut self) {
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl State {
+    fn handle(&self, request: Request) {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        let url = Url::parse(&format!("http://localhost{}", request.url()));
+        let (status, body) = match url {
+            Ok(url) => self.route(&url),
+            Err(err) => (400, json!({"code": -400, "message": err.to_string()})),
+        };
+        if self.verbose {
+            println!("{} {} -> {status}", request.method(), request.url());
+        }
+        let header = Header::from_bytes("Content-Type", "application/json; charset=utf-8")
+            .expect("静态响应头总是有效");
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        let _ = request.respond(response);
+    }
+
+    fn route(&self, url: &Url) -> (u16, Value) {
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let number = |key: &str| query.get(key).and_then(|value| value.parse::<i64>().ok());
+        let endpoint = match url.path() {
+            INFO_PATH => Endpoint::Info,
+            LIST_PATH => Endpoint::List,
+            CREATED_LIST_PATH => {
+                self.delay(None);
+                return ok(self.created_list(number("up_mid").unwrap_or_default()));
+            }
+            _ => return (404, json!({"code": NOT_FOUND_CODE, "message": "啥都木有"})),
+        };
+
+        let media_id = number("media_id").unwrap_or_default();
+        let Some(folder) = self.scenario.folder(media_id) else {
+            self.delay(None);
+            return ok_code(NOT_FOUND_CODE, "啥都木有");
+        };
+        self.delay(folder.latency_ms);
+        let page = number("pn").map(|pn| pn.max(1) as u32);
+        if let Some(failure) = self.injected_error(media_id, endpoint, page) {
+            return failure;
+        }
+        match endpoint {
+            Endpoint::Info => ok(folder.info_json()),
+            _ => {
+                let page_size = number("ps").map_or(DEFAULT_PAGE_SIZE, |ps| ps.max(1) as u32);
+                ok(folder.page_json(page.unwrap_or(1), page_size))
+            }
+        }
+    }
+
+    fn created_list(&self, up_mid: i64) -> Value {
+        let list: Vec<Value> = self
+            .scenario
+            .folders
+            .iter()
+            .filter(|folder| folder.mid == up_mid)
+            .map(|folder| folder.info_json())
+            .collect();
+        json!({"count": list.len(), "list": list})
+    }
+
+    fn injected_error(
+        &self,
+        media_id: i64,
+        endpoint: Endpoint,
+        page: Option<u32>,
+    ) -> Option<(u16, Value)> {
+        let folder = self.scenario.folder(media_id)?;
+        let page = (endpoint == Endpoint::List).then_some(page.unwrap_or(1));
+        let mut hits = self.rule_hits.lock().unwrap_or_else(|err| err.into_inner());
+        for (index, rule) in folder.errors.iter().enumerate() {
+            if !rule.matches(endpoint, page) {
+                continue;
+            }
+            let count = hits.entry((media_id, index)).or_insert(0);
+            if rule.times.is_some_and(|times| *count >= times) {
+                continue;
+            }
+            *count += 1;
+            let message = rule.message.as_deref().unwrap_or("模拟错误");
+            if let Some(status) = rule.http_status {
+                return Some((
+                    status,
+                    json!({"code": -(status as i32), "message": message}),
+                ));
+            }
+            return Some(ok_code(rule.code.unwrap_or(-500), message));
+        }
+        None
+    }
+
+    fn delay(&self, override_ms: Option<u64>) {
+        let millis = override_ms.unwrap_or(self.scenario.latency_ms);
+        if millis > 0 {
+            thread::sleep(Duration::from_millis(millis));
+        }
+    }
+}
+
+fn ok(data: Value) -> (u16, Value) {
+    (
+        200,
+        json!({"code": 0, "message": "0", "ttl": 1, "data": data}),
+    )
+}
+
+/// B 站业务错误同样以 HTTP 200 返回。
+fn ok_code(code: i32, message: &str) -> (u16, Value) {
+    (200, json!({"code": code, "message": message, "ttl": 1}))
+}
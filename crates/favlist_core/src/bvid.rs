@@ -0,0 +1,72 @@
+use crate::errors::FavlistError;
+
+const XOR_CODE: u64 = 23442827791579;
+const MASK_CODE: u64 = 2251799813685247;
+const MAX_AID: u64 = 1 << 51;
+const BASE: u64 = 58;
+const ALPHABET: &[u8; 58] = b"FcwAPNKTMug3GV5Lj7EJnHpWsx4tb8haYeviqBz6rkCy12mUSDQX9RdoZf";
+const BV_LEN: usize = 12;
+
+/// av 号转 BV 号，仅接受 `1..2^51` 范围内的 aid。
+pub fn av_to_bv(aid: u64) -> Result<String, FavlistError> {
+    if aid == 0 || aid >= MAX_AID {
+        return Err(FavlistError::InvalidVideoId(format!("av{aid}")));
+    }
+    let mut bytes = *b"BV1000000000";
+    let mut tmp = (MAX_AID | aid) ^ XOR_CODE;
+    let mut index = BV_LEN - 1;
+    while tmp > 0 {
+        bytes[index] = ALPHABET[(tmp % BASE) as usize];
+        tmp /= BASE;
+        index -= 1;
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// BV 号转 av 号；`BV` 前缀不区分大小写，其余字符区分大小写。
+pub fn bv_to_av(bvid: &str) -> Result<u64, FavlistError> {
+    let invalid = || FavlistError::InvalidVideoId(bvid.to_string());
+    let trimmed = bvid.trim();
+    let mut bytes: [u8; BV_LEN] = trimmed.as_bytes().try_into().map_err(|_| invalid())?;
+    if !bytes[..2].eq_ignore_ascii_case(b"BV") || bytes[2] != b'1' {
+        return Err(invalid());
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+    let mut tmp = 0u64;
+    for byte in &bytes[3..] {
+        let digit = ALPHABET
+            .iter()
+            .position(|candidate| candidate == byte)
+            .ok_or_else(invalid)?;
+        tmp = tmp * BASE + digit as u64;
+    }
+    Ok((tmp & MASK_CODE) ^ XOR_CODE)
+}
+
+/// 把 `BV…`/`bv…` 与 `av<数字>` 统一为 `BV` 开头的规范形式，无法识别时返回 `None`。
+///
+/// 已是 BV 形式的 ID 只规范前缀大小写，不做往返校验，以免拒绝历史数据中的非标准 BV 号。
+pub fn normalize_video_id(id: &str) -> Option<String> {
+    let trimmed = id.trim();
+    let prefix = trimmed.get(..2)?;
+    let rest = &trimmed[2..];
+    if prefix.eq_ignore_ascii_case("bv") {
+        (rest.len() == BV_LEN - 2 && rest.chars().all(|ch| ch.is_ascii_alphanumeric()))
+            .then(|| format!("BV{rest}"))
+    } else if prefix.eq_ignore_ascii_case("av") {
+        if rest.is_empty() || !rest.chars().all(|ch| ch.is_ascii_digit()) {
+            return None;
+        }
+        av_to_bv(rest.parse().ok()?).ok()
+    } else {
+        None
+    }
+}
+
+/// 规范化失败时保留原值（去除首尾空白），用于不应丢弃条目的场景。
+pub fn canonical_video_id(id: &str) -> String {
+    normalize_video_id(id).unwrap_or_else(|| id.trim().to_string())
+}
@@ -1,18 +1,44 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue},
-    Client,
+    header::{HeaderMap, HeaderName, HeaderValue, LOCATION},
+    redirect, Client,
 };
 use serde::de::DeserializeOwned;
+use url::Url;
 
+use crate::cookies::load_cookie_file;
 use crate::errors::FavlistError;
-use crate::models::{ApiResponse, FolderInfo, FolderInfoPayload, ResourceListPayload};
+use crate::fixtures::{load_fixture, save_fixture, Fixture, FixtureMode};
+use crate::models::{
+    ApiResponse, CollectedSeasonPayload, CreatedFoldersPayload, FolderInfo, FolderInfoPayload,
+    HistoryCursor, HistoryPayload, ResourceListPayload, SeasonArchivesPayload,
+    SeriesArchivesPayload, SeriesInfoPayload, Uploader, VideoItem, WatchLaterPayload,
+};
+use crate::proxy::ProxyConfig;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{RetryEvent, RetryObserver, RetryPolicy};
+use crate::source::{
+    invalid_source, parse_source_ref, FavSource, SourceRef, FORM_SHORT_LINK, HISTORY_LIST_ID,
+    WATCH_LATER_LIST_ID,
+};
 
 const DEFAULT_BASE_URL: &str = "https://api.bilibili.com";
+/// 账号未登录；携带 Cookie 时出现说明 Cookie 已失效。
+const NOT_LOGGED_IN_CODE: i32 = -101;
+const DEFAULT_SHORT_LINK_BASE: &str = "https://b23.tv";
 const INFO_PATH: &str = "/x/v3/fav/folder/info";
 const LIST_PATH: &str = "/x/v3/fav/resource/list";
+const CREATED_LIST_PATH: &str = "/x/v3/fav/folder/created/list-all";
+const COLLECTED_SEASON_PATH: &str = "/x/space/fav/season/list";
+const SEASON_ARCHIVES_PATH: &str = "/x/polymer/web-space/seasons_archives_list";
+const SERIES_INFO_PATH: &str = "/x/series/series";
+const SERIES_ARCHIVES_PATH: &str = "/x/series/archives";
+const WATCH_LATER_PATH: &str = "/x/v2/history/toview";
+const HISTORY_PATH: &str = "/x/web-interface/history/cursor";
 
 pub const DEFAULT_HEADERS: [(&str, &str); 2] = [
     (
@@ -26,8 +52,18 @@ pub const DEFAULT_HEADERS: [(&str, &str); 2] = [
 pub struct ClientOptions {
     pub timeout: Duration,
     pub cookie: Option<String>,
+    /// Netscape `cookies.txt` 或 BBDown 的 `BBDown.data`；`cookie` 为空时从此文件读取。
+    pub cookie_file: Option<PathBuf>,
     pub extra_headers: HashMap<String, String>,
     pub base_url: Option<String>,
+    pub proxy: Option<ProxyConfig>,
+    /// 短链接服务地址，解析 `b23.tv` 短链接时以它替换原主机；默认 `https://b23.tv`。
+    pub short_link_base: Option<String>,
+    pub retry: RetryPolicy,
+    /// 传入同一个限速器即可让多个客户端共享请求预算。
+    pub rate_limiter: Option<RateLimiter>,
+    /// 录制或回放 API 请求，见 `FixtureMode`。
+    pub fixtures: Option<FixtureMode>,
 }
 
 impl Default for ClientOptions {
@@ -35,8 +71,14 @@ impl Default for ClientOptions {
         Self {
             timeout: Duration::from_secs(10),
             cookie: None,
+            cookie_file: None,
             extra_headers: HashMap::new(),
             base_url: None,
+            proxy: None,
+            short_link_base: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
+            fixtures: None,
         }
     }
 }
@@ -44,12 +86,24 @@ impl Default for ClientOptions {
 #[derive(Clone)]
 pub struct BiliFavClient {
     client: Client,
+    /// 不跟随跳转，用于读取短链接的 `Location`。
+    redirect_client: Client,
     options: ClientOptions,
     base_url: String,
+    retry_observer: Option<RetryObserver>,
 }
 
 impl BiliFavClient {
-    pub fn new(options: ClientOptions) -> Result<Self, FavlistError> {
+    pub fn new(mut options: ClientOptions) -> Result<Self, FavlistError> {
+        let has_inline_cookie = options
+            .cookie
+            .as_deref()
+            .is_some_and(|cookie| !cookie.trim().is_empty());
+        if !has_inline_cookie {
+            if let Some(path) = options.cookie_file.as_deref() {
+                options.cookie = Some(load_cookie_file(path)?);
+            }
+        }
         let mut headers = HeaderMap::new();
         for (name, value) in DEFAULT_HEADERS.iter() {
             headers.insert(
@@ -72,11 +126,21 @@ impl BiliFavClient {
             headers.insert(header_name, header_value);
         }
 
-        let client = Client::builder()
+        let mut builder = Client::builder()
+            .timeout(options.timeout)
+            .default_headers(headers.clone());
+        if let Some(proxy) = options.proxy.as_ref() {
+            builder = builder.proxy(proxy.to_proxy()?);
+        }
+        let client = builder.build().map_err(FavlistError::Request)?;
+        let mut redirect_builder = Client::builder()
             .timeout(options.timeout)
             .default_headers(headers)
-            .build()
-            .map_err(FavlistError::Request)?;
+            .redirect(redirect::Policy::none());
+        if let Some(proxy) = options.proxy.as_ref() {
+            redirect_builder = redirect_builder.proxy(proxy.to_proxy()?);
+        }
+        let redirect_client = redirect_builder.build().map_err(FavlistError::Request)?;
 
         let base_url = options
             .base_url
@@ -85,11 +149,70 @@ impl BiliFavClient {
 
         Ok(Self {
             client,
+            redirect_client,
             options,
             base_url,
+            retry_observer: None,
         })
     }
 
+    /// 每次安排重试前回调，便于上层展示重试次数。
+    pub fn with_retry_observer(mut self, observer: RetryObserver) -> Self {
+        self.retry_observer = Some(observer);
+        self
+    }
+
+    /// 解析任意形式的来源引用；短链接会先请求一次以取得跳转目标。
+    pub async fn resolve_source(&self, input: &str) -> Result<FavSource, FavlistError> {
+        match parse_source_ref(input)? {
+            SourceRef::ShortLink(url) => {
+                let target = self.resolve_short_link(&url).await?;
+                match parse_source_ref(&target) {
+                    Ok(SourceRef::ShortLink(_)) => Err(invalid_source(
+                        FORM_SHORT_LINK,
+                        format!("跳转目标仍是短链接: {target}"),
+                    )),
+                    Ok(source_ref) => Ok(source_ref.source().expect("非短链接总能确定来源")),
+                    Err(err) => Err(invalid_source(
+                        FORM_SHORT_LINK,
+                        format!("跳转目标 {target} 无法识别({err})"),
+                    )),
+                }
+            }
+            source_ref => Ok(source_ref.source().expect("非短链接总能确定来源")),
+        }
+    }
+
+    /// 请求短链接并返回 `Location` 指向的地址，不会跟随跳转。
+    pub async fn resolve_short_link(&self, url: &Url) -> Result<String, FavlistError> {
+        let base = self
+            .options
+            .short_link_base
+            .as_deref()
+            .unwrap_or(DEFAULT_SHORT_LINK_BASE)
+            .trim_end_matches('/');
+        let request_url = format!("{base}{}", url.path());
+        let response = self
+            .redirect_client
+            .get(&request_url)
+            .send()
+            .await
+            .map_err(|err| invalid_source(FORM_SHORT_LINK, format!("请求 {url} 失败: {err}")))?;
+        let status = response.status();
+        if !status.is_redirection() {
+            return Err(invalid_source(
+                FORM_SHORT_LINK,
+                format!("{url} 未返回跳转(HTTP {})", status.as_u16()),
+            ));
+        }
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| invalid_source(FORM_SHORT_LINK, format!("{url} 的跳转缺少Location")))
+    }
+
     pub async fn get_folder_info(&self, media_id: i64) -> Result<FolderInfo, FavlistError> {
         let payload: FolderInfoPayload = self
             .request(
@@ -100,52 +223,391 @@ impl BiliFavClient {
         Ok(payload.into_folder_info(media_id))
     }
 
+    /// 列出用户创建的全部收藏夹（不含收藏的他人收藏夹）。
+    pub async fn list_created_folders(&self, up_mid: i64) -> Result<Vec<FolderInfo>, FavlistError> {
+        let payload: CreatedFoldersPayload = self
+            .request(
+                &format!("{}{}", self.base_url, CREATED_LIST_PATH),
+                &[("up_mid", up_mid.to_string())],
+            )
+            .await?;
+        Ok(payload
+            .list
+            .into_iter()
+            .filter_map(|folder| {
+                let media_id = folder.id?;
+                let mut info = folder.into_folder_info(media_id);
+                if info.mid == 0 {
+                    info.mid = up_mid;
+                }
+                Some(info)
+            })
+            .collect())
+    }
+
+    /// 抓取单页收藏内容，`page` 从 1 开始。
+    pub async fn fetch_page(
+        &self,
+        media_id: i64,
+        page: u32,
+        page_size: u32,
+    ) -> Result<ResourceListPayload, FavlistError> {
+        self.request(
+            &format!("{}{}", self.base_url, LIST_PATH),
+            &[
+                ("media_id", media_id.to_string()),
+                ("pn", page.to_string()),
+                ("ps", page_size.to_string()),
+                ("platform", "web".to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// 获取来源的基本信息；合集与系列同样以 `FolderInfo` 表示，`media_id` 为其 ID。
+    pub async fn get_source_info(&self, source: FavSource) -> Result<FolderInfo, FavlistError> {
+        match source {
+            FavSource::Folder { media_id } => self.get_folder_info(media_id).await,
+            FavSource::UserFolders { .. } => Err(user_folders_error()),
+            FavSource::CollectedSeason { season_id } => {
+                let payload = self.fetch_collected_season(season_id, 1, 1).await?;
+                Ok(FolderInfo {
+                    media_id: season_id,
+                    fid: 0,
+                    mid: payload.info.upper.mid,
+                    title: payload.info.title,
+                    media_count: payload.info.media_count,
+                })
+            }
+            FavSource::Season { mid, season_id } => {
+                let payload = self.fetch_season_archives(mid, season_id, 1, 1).await?;
+                Ok(FolderInfo {
+                    media_id: season_id,
+                    fid: 0,
+                    mid,
+                    title: payload.meta.name,
+                    media_count: payload.meta.total.max(payload.page.total),
+                })
+            }
+            FavSource::Series { mid, series_id } => {
+                let payload: SeriesInfoPayload = self
+                    .request(
+                        &format!("{}{}", self.base_url, SERIES_INFO_PATH),
+                        &[("series_id", series_id.to_string())],
+                    )
+                    .await?;
+                Ok(FolderInfo {
+                    media_id: series_id,
+                    fid: 0,
+                    mid,
+                    title: payload.meta.name,
+                    media_count: payload.meta.total,
+                })
+            }
+            FavSource::WatchLater => {
+                let payload = self.fetch_watch_later().await?;
+                Ok(FolderInfo {
+                    media_id: WATCH_LATER_LIST_ID,
+                    fid: 0,
+                    mid: 0,
+                    title: source.kind().to_string(),
+                    media_count: payload.count,
+                })
+            }
+            FavSource::History => {
+                self.require_cookie(source)?;
+                Ok(FolderInfo {
+                    media_id: HISTORY_LIST_ID,
+                    fid: 0,
+                    mid: 0,
+                    title: source.kind().to_string(),
+                    media_count: 0,
+                })
+            }
+        }
+    }
+
+    /// 抓取任意来源的单页内容并统一为收藏夹分页结构，`page` 从 1 开始。
+    /// 稍后再看一次返回全部条目；历史记录按游标翻页，此处只支持第 1 页。
+    pub async fn fetch_source_page(
+        &self,
+        source: FavSource,
+        page: u32,
+        page_size: u32,
+    ) -> Result<ResourceListPayload, FavlistError> {
+        let fetched = i64::from(page) * i64::from(page_size);
+        match source {
+            FavSource::Folder { media_id } => self.fetch_page(media_id, page, page_size).await,
+            FavSource::UserFolders { .. } => Err(user_folders_error()),
+            FavSource::CollectedSeason { season_id } => {
+                let payload = self
+                    .fetch_collected_season(season_id, page, page_size)
+                    .await?;
+                let has_more = !payload.medias.is_empty() && fetched < payload.info.media_count;
+                Ok(ResourceListPayload {
+                    medias: payload.medias,
+                    has_more,
+                })
+            }
+            FavSource::Season { mid, season_id } => {
+                let payload = self
+                    .fetch_season_archives(mid, season_id, page, page_size)
+                    .await?;
+                let upper = Uploader {
+                    mid,
+                    name: String::new(),
+                };
+                let has_more = !payload.archives.is_empty() && fetched < payload.page.total;
+                Ok(ResourceListPayload {
+                    medias: payload
+                        .archives
+                        .into_iter()
+                        .map(|archive| archive.into_video_item(&upper))
+                        .collect(),
+                    has_more,
+                })
+            }
+            FavSource::Series { mid, series_id } => {
+                let payload: SeriesArchivesPayload = self
+                    .request(
+                        &format!("{}{}", self.base_url, SERIES_ARCHIVES_PATH),
+                        &[
+                            ("mid", mid.to_string()),
+                            ("series_id", series_id.to_string()),
+                            ("only_normal", "true".to_string()),
+                            ("sort", "desc".to_string()),
+                            ("pn", page.to_string()),
+                            ("ps", page_size.to_string()),
+                        ],
+                    )
+                    .await?;
+                let upper = Uploader {
+                    mid,
+                    name: String::new(),
+                };
+                let has_more = !payload.archives.is_empty() && fetched < payload.page.total;
+                Ok(ResourceListPayload {
+                    medias: payload
+                        .archives
+                        .into_iter()
+                        .map(|archive| archive.into_video_item(&upper))
+                        .collect(),
+                    has_more,
+                })
+            }
+            FavSource::WatchLater => {
+                let medias = if page == 1 {
+                    self.fetch_watch_later()
+                        .await?
+                        .list
+                        .into_iter()
+                        .map(|item| item.into_video_item())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                Ok(ResourceListPayload {
+                    medias,
+                    has_more: false,
+                })
+            }
+            FavSource::History => {
+                if page != 1 {
+                    return Err(FavlistError::Other(
+                        "历史记录需按游标翻页，请使用 source_pages".to_string(),
+                    ));
+                }
+                let payload = self
+                    .fetch_history(HistoryCursor::default(), page_size)
+                    .await?;
+                Ok(history_page(payload).0)
+            }
+        }
+    }
+
+    /// 按页惰性抓取收藏夹，返回的流在 `has_more` 为假或出错后结束。
+    pub fn video_pages(
+        &self,
+        media_id: i64,
+        page_size: u32,
+    ) -> BoxStream<'static, Result<ResourceListPayload, FavlistError>> {
+        self.source_pages(FavSource::Folder { media_id }, page_size)
+    }
+
+    /// 按页惰性抓取任意来源，行为与 `video_pages` 相同。
+    pub fn source_pages(
+        &self,
+        source: FavSource,
+        page_size: u32,
+    ) -> BoxStream<'static, Result<ResourceListPayload, FavlistError>> {
+        let client = self.clone();
+        if source == FavSource::History {
+            return stream::try_unfold(Some(HistoryCursor::default()), move |cursor| {
+                let client = client.clone();
+                async move {
+                    let Some(cursor) = cursor else {
+                        return Ok(None);
+                    };
+                    let payload = client.fetch_history(cursor, page_size).await?;
+                    let (page, next) = history_page(payload);
+                    Ok(Some((page, next.filter(|next| *next != cursor))))
+                }
+            })
+            .boxed();
+        }
+        stream::try_unfold(Some(1u32), move |page| {
+            let client = client.clone();
+            async move {
+                let Some(page) = page else {
+                    return Ok(None);
+                };
+                let payload = client.fetch_source_page(source, page, page_size).await?;
+                let next = payload.has_more.then_some(page + 1);
+                Ok(Some((payload, next)))
+            }
+        })
+        .boxed()
+    }
+
+    async fn fetch_watch_later(&self) -> Result<WatchLaterPayload, FavlistError> {
+        self.require_cookie(FavSource::WatchLater)?;
+        self.request(&format!("{}{}", self.base_url, WATCH_LATER_PATH), &[])
+            .await
+    }
+
+    /// 抓取游标之前的一页观看历史，首页传入默认游标。
+    pub async fn fetch_history(
+        &self,
+        cursor: HistoryCursor,
+        page_size: u32,
+    ) -> Result<HistoryPayload, FavlistError> {
+        self.require_cookie(FavSource::History)?;
+        self.request(
+            &format!("{}{}", self.base_url, HISTORY_PATH),
+            &[
+                ("max", cursor.max.to_string()),
+                ("view_at", cursor.view_at.to_string()),
+                ("type", "archive".to_string()),
+                ("ps", page_size.to_string()),
+            ],
+        )
+        .await
+    }
+
+    fn require_cookie(&self, source: FavSource) -> Result<(), FavlistError> {
+        match self.options.cookie.as_deref() {
+            Some(cookie) if !cookie.trim().is_empty() => Ok(()),
+            _ => Err(FavlistError::CookieRequired(source.kind().to_string())),
+        }
+    }
+
+    async fn fetch_collected_season(
+        &self,
+        season_id: i64,
+        page: u32,
+        page_size: u32,
+    ) -> Result<CollectedSeasonPayload, FavlistError> {
+        self.request(
+            &format!("{}{}", self.base_url, COLLECTED_SEASON_PATH),
+            &[
+                ("season_id", season_id.to_string()),
+                ("pn", page.to_string()),
+                ("ps", page_size.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// 合集按倒序抓取，使最新稿件排在前面，便于增量同步提前停止。
+    async fn fetch_season_archives(
+        &self,
+        mid: i64,
+        season_id: i64,
+        page: u32,
+        page_size: u32,
+    ) -> Result<SeasonArchivesPayload, FavlistError> {
+        self.request(
+            &format!("{}{}", self.base_url, SEASON_ARCHIVES_PATH),
+            &[
+                ("mid", mid.to_string()),
+                ("season_id", season_id.to_string()),
+                ("sort_reverse", "true".to_string()),
+                ("page_num", page.to_string()),
+                ("page_size", page_size.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// 将分页流展开为逐条视频的流。
+    pub fn video_items(
+        &self,
+        media_id: i64,
+        page_size: u32,
+    ) -> BoxStream<'static, Result<VideoItem, FavlistError>> {
+        self.video_pages(media_id, page_size)
+            .map_ok(|payload| stream::iter(payload.medias.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
     pub async fn list_videos(
         &self,
         media_id: i64,
         page_size: u32,
     ) -> Result<Vec<ResourceListPayload>, FavlistError> {
-        let mut page = 1u32;
-        let mut pages = Vec::new();
+        self.video_pages(media_id, page_size).try_collect().await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, FavlistError> {
+        let policy = &self.options.retry;
+        let max_attempts = policy.max_attempts.max(1);
+        let mut attempt = 1u32;
         loop {
-            let payload: ResourceListPayload = self
-                .request(
-                    &format!("{}{}", self.base_url, LIST_PATH),
-                    &[
-                        ("media_id", media_id.to_string()),
-                        ("pn", page.to_string()),
-                        ("ps", page_size.to_string()),
-                        ("platform", "web".to_string()),
-                    ],
-                )
-                .await?;
-            let has_more = payload.has_more;
-            pages.push(payload);
-            if !has_more {
-                break;
+            match self.request_once(url, params).await {
+                Ok(data) => return Ok(data),
+                Err(err) if attempt < max_attempts && err.is_retryable() => {
+                    let delay = policy.delay_for(attempt);
+                    attempt += 1;
+                    log::warn!("请求 {url} 失败({err})，{delay:?} 后进行第 {attempt} 次尝试");
+                    if let Some(observer) = self.retry_observer.as_ref() {
+                        observer(&RetryEvent {
+                            attempt,
+                            max_attempts,
+                            delay,
+                            reason: err.to_string(),
+                        });
+                    }
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => return Err(err),
             }
-            page += 1;
         }
-        Ok(pages)
     }
 
-    async fn request<T: DeserializeOwned>(
+    async fn request_once<T: DeserializeOwned>(
         &self,
         url: &str,
         params: &[(&str, String)],
     ) -> Result<T, FavlistError> {
-        let mut req = self.client.get(url);
-        for (k, v) in params {
-            req = req.query(&[(k, v.as_str())]);
-        }
-        let response = req.send().await.map_err(FavlistError::Request)?;
-        let status = response.status();
-        if !status.is_success() {
-            return Err(FavlistError::Other(format!("HTTP请求失败: {status}")));
+        let (status, bytes) = self.fetch_raw(url, params).await?;
+        if !(200..300).contains(&status) {
+            return Err(FavlistError::Http { status });
         }
-        let bytes = response.bytes().await.map_err(FavlistError::Request)?;
         let payload: ApiResponse<T> = serde_json::from_slice(&bytes)
             .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+        if payload.code == NOT_LOGGED_IN_CODE {
+            let message = payload.message.unwrap_or_else(|| "账号未登录".to_string());
+            return Err(if self.options.cookie.is_some() {
+                FavlistError::CookieExpired(message)
+            } else {
+                FavlistError::CookieRequired("该列表".to_string())
+            });
+        }
         if payload.code != 0 {
             return Err(FavlistError::Api {
                 code: payload.code,
@@ -157,7 +619,59 @@ impl BiliFavClient {
             .ok_or_else(|| FavlistError::InvalidJson("响应缺少data字段".to_string()))
     }
 
+    /// 回放模式直接读取录制文件；录制模式在请求完成后保存响应，非 2xx 响应同样保存。
+    async fn fetch_raw(
+        &self,
+        url: &str,
+        params: &[(&str, String)],
+    ) -> Result<(u16, Vec<u8>), FavlistError> {
+        if let Some(FixtureMode::Replay(dir)) = self.options.fixtures.as_ref() {
+            let fixture = load_fixture(dir, url, params)?;
+            return Ok((fixture.status, fixture.body_bytes()));
+        }
+        if let Some(limiter) = self.options.rate_limiter.as_ref() {
+            limiter.acquire().await;
+        }
+        let mut req = self.client.get(url);
+        for (k, v) in params {
+            req = req.query(&[(k, v.as_str())]);
+        }
+        let response = req.send().await.map_err(FavlistError::Request)?;
+        let status = response.status().as_u16();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(FavlistError::Request)?
+            .to_vec();
+        if let Some(FixtureMode::Record(dir)) = self.options.fixtures.as_ref() {
+            save_fixture(dir, &Fixture::new(url, params, status, &bytes))?;
+        }
+        Ok((status, bytes))
+    }
+
     pub fn options(&self) -> &ClientOptions {
         &self.options
     }
 }
+
+/// 只保留稿件类历史，并给出下一页的游标；本页为空时没有下一页。
+fn history_page(payload: HistoryPayload) -> (ResourceListPayload, Option<HistoryCursor>) {
+    let next = (!payload.list.is_empty() && payload.cursor.max != 0).then_some(payload.cursor);
+    let medias = payload
+        .list
+        .into_iter()
+        .filter(|item| item.history.business.is_empty() || item.history.business == "archive")
+        .map(|item| item.into_video_item())
+        .collect();
+    (
+        ResourceListPayload {
+            medias,
+            has_more: next.is_some(),
+        },
+        next,
+    )
+}
+
+fn user_folders_error() -> FavlistError {
+    FavlistError::InvalidUrl("用户空间链接包含多个收藏夹，请先列出收藏夹再逐个导出".to_string())
+}
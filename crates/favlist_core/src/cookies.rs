@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::Path;
+
+use crate::errors::FavlistError;
+use crate::login::LoginCredentials;
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+/// BBDown.data 由扫码回调链接的查询串改写而来，其中这些键不是 Cookie。
+const BBDOWN_NON_COOKIE_KEYS: [&str; 3] = ["gourl", "Expires", "first_domain"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieFileFormat {
+    /// 浏览器扩展导出的 Netscape `cookies.txt`。
+    Netscape,
+    /// BBDown 登录后写入的 `BBDown.data`，即以 `;` 分隔的 `key=value` 串。
+    BBDown,
+    /// 扫码登录保存的 `LoginCredentials` JSON。
+    Credentials,
+}
+
+impl CookieFileFormat {
+    pub fn detect(content: &str) -> Self {
+        if content.trim_start().starts_with('{') {
+            return CookieFileFormat::Credentials;
+        }
+        let is_netscape = content.lines().any(|line| {
+            line.starts_with(NETSCAPE_HEADER)
+                || line.starts_with(HTTP_ONLY_PREFIX)
+                || line.split('\t').count() == 7
+        });
+        if is_netscape {
+            CookieFileFormat::Netscape
+        } else {
+            CookieFileFormat::BBDown
+        }
+    }
+}
+
+/// 读取 Cookie 文件并转换为 `Cookie` 请求头的值，格式按内容自动识别。
+pub fn load_cookie_file(path: &Path) -> Result<String, FavlistError> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        FavlistError::Other(format!("读取Cookie文件 {} 失败: {err}", path.display()))
+    })?;
+    let cookie = match CookieFileFormat::detect(&content) {
+        CookieFileFormat::Netscape => {
+            parse_netscape_cookies(&content, chrono::Utc::now().timestamp())
+        }
+        CookieFileFormat::BBDown => parse_bbdown_data(&content),
+        CookieFileFormat::Credentials => serde_json::from_str::<LoginCredentials>(&content)
+            .map_err(|err| FavlistError::InvalidJson(err.to_string()))?
+            .cookie_header(),
+    };
+    if cookie.is_empty() {
+        return Err(FavlistError::CookieExpired(format!(
+            "Cookie文件 {} 中没有可用的B站Cookie",
+            path.display()
+        )));
+    }
+    Ok(cookie)
+}
+
+/// 仅保留 `bilibili.com` 域下未过期的条目；过期时间为 0 的会话 Cookie 始终保留。
+pub fn parse_netscape_cookies(content: &str, now: i64) -> String {
+    let mut pairs = Vec::new();
+    for line in content.lines() {
+        let line = line
+            .strip_prefix(HTTP_ONLY_PREFIX)
+            .unwrap_or(line)
+            .trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [domain, _, _, _, expires, name, value] = fields.as_slice() else {
+            continue;
+        };
+        if !domain.trim_start_matches('.').ends_with("bilibili.com") {
+            continue;
+        }
+        let expires = expires.trim().parse::<i64>().unwrap_or(0);
+        if expires != 0 && expires < now {
+            continue;
+        }
+        pairs.push(format!("{}={}", name.trim(), value.trim()));
+    }
+    pairs.join("; ")
+}
+
+pub fn parse_bbdown_data(content: &str) -> String {
+    content
+        .split([';', '\n', '\r'])
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty() && !BBDOWN_NON_COOKIE_KEYS.contains(&name))
+                .then(|| format!("{name}={}", value.trim()))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
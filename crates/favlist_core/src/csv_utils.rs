@@ -1,17 +1,198 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use encoding_rs::{Encoding, UTF_8};
 use encoding_rs_io::DecodeReaderBytesBuilder;
 
+use crate::bvid::canonical_video_id;
 use crate::errors::FavlistError;
-use crate::models::VideoEntry;
+use crate::models::{VideoEntry, VideoStatus};
 
 pub type CsvRow = HashMap<String, String>;
 
-pub const FIELDNAMES: [&str; 4] = ["timestamp", "bv_id", "title", "fav_name"];
+/// 当前 CSV 表头版本；表头变化时递增并在 [`schema_columns`] 中登记。
+pub const CSV_SCHEMA_VERSION: u32 = 3;
+
+pub const FIELDNAMES: [&str; 12] = [
+    "timestamp",
+    "bv_id",
+    "title",
+    "fav_name",
+    "status",
+    "aid",
+    "upper_mid",
+    "upper_name",
+    "duration",
+    "page_count",
+    "fav_time",
+    "pubtime",
+];
+
+/// 各版本的表头：v1 为最初的四列，v2 增加 `status`，v3 增加视频元数据。
+pub fn schema_columns(version: u32) -> Option<&'static [&'static str]> {
+    match version {
+        1 => Some(&FIELDNAMES[..4]),
+        2 => Some(&FIELDNAMES[..5]),
+        3 => Some(&FIELDNAMES[..]),
+        _ => None,
+    }
+}
+
+/// Python 旧版及手工整理的表格中出现过的列名别名。
+const COLUMN_ALIASES: [(&str, &[&str]); 4] = [
+    ("bv_id", &["BV号", "视频BV号"]),
+    ("title", &["标题", "视频标题"]),
+    ("fav_name", &["收藏夹", "收藏夹名称"]),
+    ("timestamp", &["时间", "抓取时间"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvSchema {
+    /// 与某个已登记版本的表头完全一致。
+    Versioned(u32),
+    /// 使用中文列名（`BV号`/`视频BV号` 等）的旧表头，版本记为 0。
+    Legacy,
+    /// 含自定义列的表头，不自动迁移，仅按列名对齐写入。
+    Unknown,
+}
+
+impl CsvSchema {
+    pub fn version(self) -> Option<u32> {
+        match self {
+            CsvSchema::Versioned(version) => Some(version),
+            CsvSchema::Legacy => Some(0),
+            CsvSchema::Unknown => None,
+        }
+    }
+
+    pub fn is_current(self) -> bool {
+        self == CsvSchema::Versioned(CSV_SCHEMA_VERSION)
+    }
+}
+
+pub fn detect_schema<S: AsRef<str>>(headers: &[S]) -> CsvSchema {
+    let names: Vec<&str> = headers.iter().map(|name| name.as_ref().trim()).collect();
+    for version in 1..=CSV_SCHEMA_VERSION {
+        if schema_columns(version).is_some_and(|columns| columns == names.as_slice()) {
+            return CsvSchema::Versioned(version);
+        }
+    }
+    let has_alias = names.iter().any(|name| canonical_column(name) != *name);
+    let all_known = names
+        .iter()
+        .all(|name| FIELDNAMES.contains(&canonical_column(name)));
+    if has_alias && all_known {
+        CsvSchema::Legacy
+    } else {
+        CsvSchema::Unknown
+    }
+}
+
+fn canonical_column(name: &str) -> &str {
+    COLUMN_ALIASES
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&name))
+        .map(|(canonical, _)| *canonical)
+        .unwrap_or(name)
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvMigration {
+    pub from_version: u32,
+    pub backup_path: PathBuf,
+    pub rows: usize,
+}
+
+/// 将旧版本 CSV 原子地重写为当前表头，并保留 `<文件名>.v<旧版本>.bak.csv` 备份；
+/// 同名备份已存在时改用 `.v<旧版本>.bak-2.csv` 等后缀，不覆盖此前的备份。
+///
+/// 文件不存在、已是当前版本或表头无法识别时返回 `Ok(None)`。
+pub fn migrate_csv(path: &Path, encoding: &str) -> Result<Option<CsvMigration>, FavlistError> {
+    let enc = resolve_encoding(encoding)?;
+    let Some(headers) = read_header(path, enc)? else {
+        return Ok(None);
+    };
+    let schema = detect_schema(&headers);
+    let Some(from_version) = schema.version().filter(|_| !schema.is_current()) else {
+        return Ok(None);
+    };
+
+    let rows = read_csv_rows(path, encoding)?;
+    let tmp_path = sibling_path(path, "migrating.tmp");
+    {
+        let file = File::create(&tmp_path)?;
+        let mut writer = BufWriter::new(file);
+        write_record(&mut writer, enc, &FIELDNAMES)?;
+        for row in &rows {
+            let canonical: CsvRow = row
+                .iter()
+                .map(|(key, value)| (canonical_column(key).to_string(), value.clone()))
+                .collect();
+            let status = migrated_status(&canonical);
+            let bv_id = canonical
+                .get("bv_id")
+                .map(|value| canonical_video_id(value))
+                .unwrap_or_default();
+            let record: Vec<&str> = FIELDNAMES
+                .iter()
+                .map(|column| match *column {
+                    "status" => status.as_str(),
+                    "bv_id" => bv_id.as_str(),
+                    other => canonical.get(other).map(String::as_str).unwrap_or(""),
+                })
+                .collect();
+            write_record(&mut writer, enc, &record)?;
+        }
+        writer.flush()?;
+    }
+
+    let backup_path = unused_backup_path(path, from_version);
+    std::fs::copy(path, &backup_path)?;
+    std::fs::rename(&tmp_path, path)?;
+    log::info!(
+        "CSV {} 已从 v{from_version} 迁移至 v{CSV_SCHEMA_VERSION}，备份位于 {}",
+        path.display(),
+        backup_path.display()
+    );
+    Ok(Some(CsvMigration {
+        from_version,
+        backup_path,
+        rows: rows.len(),
+    }))
+}
+
+fn migrated_status(row: &CsvRow) -> VideoStatus {
+    let recorded = row
+        .get("status")
+        .map(|value| VideoStatus::parse(value))
+        .unwrap_or_default();
+    if recorded.is_available() {
+        VideoStatus::classify(0, row.get("title").map(String::as_str).unwrap_or(""))
+    } else {
+        recorded
+    }
+}
+
+fn unused_backup_path(path: &Path, from_version: u32) -> PathBuf {
+    let first = sibling_path(path, &format!("v{from_version}.bak.csv"));
+    if !first.exists() {
+        return first;
+    }
+    (2u32..)
+        .map(|n| sibling_path(path, &format!("v{from_version}.bak-{n}.csv")))
+        .find(|candidate| !candidate.exists())
+        .expect("备份序号不会耗尽")
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "favlist".to_string());
+    path.with_file_name(format!("{stem}.{suffix}"))
+}
 
 fn resolve_encoding(label: &str) -> Result<&'static Encoding, FavlistError> {
     Encoding::for_label(label.as_bytes())
@@ -32,7 +213,7 @@ pub fn load_existing_bv_ids(path: &Path, encoding: &str) -> Result<HashSet<Strin
     let candidate_fields = ["bv_id", "BV号", "视频BV号"];
     let headers = reader.headers()?.clone();
     let bv_index = headers.iter().enumerate().find_map(|(idx, name)| {
-        if candidate_fields.iter().any(|candidate| candidate == &name) || name == "bv_id" {
+        if candidate_fields.iter().any(|candidate| candidate == &name) {
             Some(idx)
         } else {
             None
@@ -49,7 +230,7 @@ pub fn load_existing_bv_ids(path: &Path, encoding: &str) -> Result<HashSet<Strin
         if let Some(value) = record.get(index) {
             let trimmed = value.trim();
             if !trimmed.is_empty() {
-                existing.insert(trimmed.to_string());
+                existing.insert(canonical_video_id(trimmed));
             }
         }
     }
@@ -80,40 +261,94 @@ pub fn read_csv_rows(path: &Path, encoding: &str) -> Result<Vec<CsvRow>, Favlist
     Ok(rows)
 }
 
+/// 追加条目，写入前先把旧版表头迁移到当前版本。
 pub fn write_entries(
     path: &Path,
     encoding: &str,
     entries: &[VideoEntry],
+) -> Result<usize, FavlistError> {
+    migrate_csv(path, encoding)?;
+    append_entries(path, encoding, entries, &mut None)
+}
+
+/// 按表头列顺序追加条目，不检查表头版本。`columns` 缓存表头：为空时读取已有表头
+/// （文件不存在则写入当前表头）并回填，之后的调用不再重新读取。
+pub(crate) fn append_entries(
+    path: &Path,
+    encoding: &str,
+    entries: &[VideoEntry],
+    columns: &mut Option<Vec<String>>,
 ) -> Result<usize, FavlistError> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     let enc = resolve_encoding(encoding)?;
-    let is_new_file = !path.exists();
+    if !path.exists() {
+        *columns = None;
+    }
+    if columns.is_none() {
+        // 无法识别版本的文件按其表头列顺序写入，避免列数不一致导致错位。
+        *columns = read_header(path, enc)?;
+    }
     let file = OpenOptions::new().create(true).append(true).open(path)?;
     let mut writer = BufWriter::new(file);
 
-    if is_new_file {
-        write_record(&mut writer, enc, &FIELDNAMES)?;
-    }
+    let columns = match columns {
+        Some(columns) => columns,
+        None => {
+            write_record(&mut writer, enc, &FIELDNAMES)?;
+            columns.insert(FIELDNAMES.iter().map(|name| name.to_string()).collect())
+        }
+    };
 
     for entry in entries {
-        write_record(
-            &mut writer,
-            enc,
-            &[
-                entry.timestamp.as_str(),
-                entry.bv_id.as_str(),
-                entry.title.as_str(),
-                entry.fav_title.as_str(),
-            ],
-        )?;
+        let values: Vec<String> = columns
+            .iter()
+            .map(|column| entry_value(entry, column))
+            .collect();
+        let record: Vec<&str> = values.iter().map(String::as_str).collect();
+        write_record(&mut writer, enc, &record)?;
     }
 
     writer.flush()?;
     Ok(entries.len())
 }
 
+fn read_header(
+    path: &Path,
+    encoding: &'static Encoding,
+) -> Result<Option<Vec<String>>, FavlistError> {
+    if !path.exists() || std::fs::metadata(path)?.len() == 0 {
+        return Ok(None);
+    }
+    let file = File::open(path)?;
+    let mut reader = csv::Reader::from_reader(
+        DecodeReaderBytesBuilder::new()
+            .encoding(Some(encoding))
+            .build(file),
+    );
+    let headers = reader.headers()?;
+    Ok(Some(headers.iter().map(|name| name.to_string()).collect()))
+}
+
+fn entry_value(entry: &VideoEntry, column: &str) -> String {
+    match canonical_column(column) {
+        "timestamp" => entry.timestamp.clone(),
+        "bv_id" => canonical_video_id(&entry.bv_id),
+        "title" => entry.title.clone(),
+        "fav_name" => entry.fav_title.clone(),
+        "status" => entry.status.as_str().to_string(),
+        "aid" => entry.aid.map(|aid| aid.to_string()).unwrap_or_default(),
+        "upper_mid" => entry.upper.mid.to_string(),
+        "upper_name" => entry.upper.name.clone(),
+        "duration" => entry.duration.to_string(),
+        "page_count" => entry.page_count.to_string(),
+        "fav_time" => entry.fav_time.to_string(),
+        "pubtime" => entry.pubtime.to_string(),
+        _ => String::new(),
+    }
+}
+
 fn write_record<W: Write>(
     writer: &mut W,
     encoding: &'static Encoding,
@@ -1,4 +1,5 @@
 use std::io;
+use std::path::PathBuf;
 
 use thiserror::Error;
 
@@ -6,8 +7,20 @@ use thiserror::Error;
 pub enum FavlistError {
     #[error("无法解析收藏夹链接: {0}")]
     InvalidUrl(String),
+    #[error("无法解析{form}: {reason}")]
+    InvalidSource { form: String, reason: String },
     #[error("网络请求失败: {0}")]
     Request(#[from] reqwest::Error),
+    #[error("HTTP请求失败: {status}")]
+    Http { status: u16 },
+    #[error("无效的视频ID: {0}")]
+    InvalidVideoId(String),
+    #[error("{0}需要登录Cookie")]
+    CookieRequired(String),
+    #[error("登录Cookie已失效，请重新登录或更新Cookie文件: {0}")]
+    CookieExpired(String),
+    #[error("登录失败: {0}")]
+    Login(String),
     #[error("API响应错误(code={code}, message={message})")]
     Api { code: i32, message: String },
     #[error("响应不是有效的JSON: {0}")]
@@ -16,8 +29,12 @@ pub enum FavlistError {
     Io(#[from] io::Error),
     #[error("CSV解析失败: {0}")]
     Csv(#[from] csv::Error),
+    #[error("SQLite操作失败: {0}")]
+    Sqlite(#[from] rusqlite::Error),
     #[error("编码转换失败: {0}")]
     Encoding(String),
+    #[error("录制/回放数据错误: {0}")]
+    Fixture(String),
     #[error("未知错误: {0}")]
     Other(String),
 }
@@ -28,16 +45,25 @@ pub enum ExportError {
     Core(#[from] FavlistError),
     #[error("执行业务流程失败: {0}")]
     Context(String),
+    /// 导出被取消；此前抓取的 `written` 条新增条目已写入 `path`。
+    #[error("导出已取消，{written} 条新增条目已写入 {}", path.display())]
+    Cancelled { written: usize, path: PathBuf },
 }
 
 impl ExportError {
+    /// 取消不是失败，原样返回以便调用方识别。
     pub fn context<T: Into<String>>(self, message: T) -> Self {
         let message = message.into();
         match self {
+            ExportError::Cancelled { .. } => self,
             ExportError::Core(err) => ExportError::Context(format!("{message}: {err}")),
             ExportError::Context(existing) => {
                 ExportError::Context(format!("{message}: {existing}"))
             }
         }
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ExportError::Cancelled { .. })
+    }
 }
@@ -1,36 +1,149 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::runtime::Builder;
+use futures::StreamExt;
+use tokio::runtime::{Builder, Runtime};
+use tokio_util::sync::CancellationToken;
 
 use crate::client::{BiliFavClient, ClientOptions};
-use crate::csv_utils::{load_existing_bv_ids, write_entries};
+use crate::csv_utils::CsvMigration;
 use crate::errors::{ExportError, FavlistError};
-use crate::models::{FolderInfo, VideoEntry, VideoItem};
-use crate::timestamp::{current_timestamp, parse_media_id};
+use crate::fixtures::FixtureMode;
+use crate::models::{FolderInfo, InvalidVideoPolicy, VideoEntry, VideoItem};
+use crate::proxy::ProxyConfig;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{RetryEvent, RetryPolicy};
+use crate::sink::{create_sink, CsvSink, ExportFormat, ExportSink};
+use crate::source::{folder_url, FavSource};
+use crate::timestamp::current_timestamp;
 
+/// 导出过程中的事件，按发生顺序回调；`Display` 输出适合直接写入日志的中文描述。
 #[derive(Debug, Clone)]
-pub struct ExportProgress {
-    pub current: u64,
-    pub total: Option<u64>,
+pub enum ExportEvent {
+    /// 已获取收藏夹信息，`total` 为接口给出的条目总数（未知时为空）。
+    FolderInfo {
+        folder: FolderInfo,
+        total: Option<u64>,
+    },
+    /// 抓取到一页，`processed` 为含本页在内累计的条目数。
+    PageFetched {
+        page: u32,
+        items: usize,
+        processed: u64,
+        total: Option<u64>,
+    },
+    /// 条目已存在于输出文件（或本次已出现过），不再写入。
+    DuplicateSkipped { bvid: String },
+    /// 请求失败，等待 `retry.delay` 后重试；`retries` 为本次导出累计的重试次数。
+    RetryScheduled { retry: RetryEvent, retries: u64 },
+    /// 开始把第 `page` 页的 `count` 条新增条目写入 `path`。
+    WriteStarted {
+        page: u32,
+        count: usize,
+        path: PathBuf,
+    },
+    /// 导出成功结束；取消或失败时不会发出。
+    Finished {
+        processed: u64,
+        new_entries: usize,
+        retries: u64,
+        stopped_early: bool,
+    },
 }
 
-pub type ProgressCallback = Arc<dyn Fn(ExportProgress) + Send + Sync + 'static>;
+impl fmt::Display for ExportEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportEvent::FolderInfo { folder, total } => {
+                write!(f, "已获取收藏夹「{}」信息", folder.title)?;
+                if let Some(total) = total {
+                    write!(f, "，共 {total} 条")?;
+                }
+                Ok(())
+            }
+            ExportEvent::PageFetched {
+                page,
+                items,
+                processed,
+                total,
+            } => {
+                write!(f, "已抓取第 {page} 页（{items} 条），累计 {processed}")?;
+                match total {
+                    Some(total) => write!(f, "/{total} 条"),
+                    None => write!(f, " 条"),
+                }
+            }
+            ExportEvent::DuplicateSkipped { bvid } => write!(f, "跳过已存在的 {bvid}"),
+            ExportEvent::RetryScheduled { retry, .. } => write!(
+                f,
+                "{}，{} 毫秒后进行第 {}/{} 次尝试",
+                retry.reason,
+                retry.delay.as_millis(),
+                retry.attempt,
+                retry.max_attempts
+            ),
+            ExportEvent::WriteStarted { count, path, .. } => {
+                write!(f, "正在写入 {count} 条新增条目到 {}", path.display())
+            }
+            ExportEvent::Finished {
+                processed,
+                new_entries,
+                retries,
+                stopped_early,
+            } => {
+                write!(
+                    f,
+                    "导出完成：处理 {processed} 条，新增 {new_entries} 条，重试 {retries} 次"
+                )?;
+                if *stopped_early {
+                    write!(f, "（增量同步提前结束）")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub type ExportCallback = Arc<dyn Fn(ExportEvent) + Send + Sync + 'static>;
+
+/// 增量同步：收藏夹按收藏时间倒序，遇到已知条目后即可停止翻页。
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalOptions {
+    /// 连续遇到多少个已存在的 BV 号后停止翻页，0 表示不按此条件停止。
+    pub stop_after_known: usize,
+    /// 收藏时间不晚于该值（Unix 秒）的条目视为已同步过。
+    pub since_fav_time: Option<i64>,
+}
 
 #[derive(Clone)]
 pub struct ExportOptions {
     pub fav_url: String,
+    /// 输出路径；`format` 非 CSV 时同样写入此路径。
     pub csv_path: PathBuf,
+    pub format: ExportFormat,
     pub encoding: String,
     pub page_size: u32,
     pub cookie: Option<String>,
+    pub cookie_file: Option<PathBuf>,
     pub timeout_secs: u64,
     pub timestamp: Option<String>,
     pub extra_headers: HashMap<String, String>,
     pub base_url: Option<String>,
-    pub progress_callback: Option<ProgressCallback>,
+    pub proxy: Option<ProxyConfig>,
+    pub short_link_base: Option<String>,
+    pub retry: RetryPolicy,
+    pub rate_limiter: Option<RateLimiter>,
+    pub fixtures: Option<FixtureMode>,
+    pub incremental: Option<IncrementalOptions>,
+    pub invalid_policy: InvalidVideoPolicy,
+    pub event_callback: Option<ExportCallback>,
+    /// 触发后在当前请求处停止：已抓取的条目照常写入输出文件，随后返回 `ExportError::Cancelled`。
+    pub cancel: Option<CancellationToken>,
 }
 
 impl Default for ExportOptions {
@@ -38,14 +151,24 @@ impl Default for ExportOptions {
         Self {
             fav_url: String::new(),
             csv_path: PathBuf::new(),
+            format: ExportFormat::default(),
             encoding: "utf-8".to_string(),
             page_size: 40,
             cookie: None,
+            cookie_file: None,
             timeout_secs: 10,
             timestamp: None,
             extra_headers: HashMap::new(),
             base_url: None,
-            progress_callback: None,
+            proxy: None,
+            short_link_base: None,
+            retry: RetryPolicy::default(),
+            rate_limiter: None,
+            fixtures: None,
+            incremental: None,
+            invalid_policy: InvalidVideoPolicy::default(),
+            event_callback: None,
+            cancel: None,
         }
     }
 }
@@ -55,91 +178,340 @@ pub struct ExportResult {
     pub csv_path: PathBuf,
     pub folder_info: FolderInfo,
     pub new_entries: Vec<VideoEntry>,
+    /// 本次遇到的失效条目（`InvalidVideoPolicy::Skip` 时为空）。
+    pub invalid_entries: Vec<VideoEntry>,
+    /// 本次抓取到的全部条目（含已导出过的与失效条目），供媒体库刷新 `last_seen`。
+    pub seen_entries: Vec<VideoEntry>,
     pub timestamp: String,
     pub processed_count: u64,
     pub total_count: Option<u64>,
+    pub retry_count: u64,
+    /// 增量模式下提前结束翻页时为真。
+    pub stopped_early: bool,
+    /// 本次抓取到的最新收藏时间，可作为下次增量同步的 `since_fav_time`。
+    pub latest_fav_time: Option<i64>,
+    /// 目标 CSV 为旧版表头时自动迁移的记录。
+    pub migration: Option<CsvMigration>,
+}
+
+pub async fn export_favlist(options: ExportOptions) -> Result<ExportResult, ExportError> {
+    let client = BiliFavClient::new(options.client_options())?;
+    export_favlist_with_client(&client, options).await
 }
 
-pub async fn export_favlist(mut options: ExportOptions) -> Result<ExportResult, ExportError> {
-    let media_id = parse_media_id(&options.fav_url)?;
+/// 使用已有客户端导出，便于多个导出任务共享连接池与限速器。
+/// `options` 中 Cookie、代理、`base_url` 等客户端设置被忽略，以 `client` 为准。
+pub async fn export_favlist_with_client(
+    client: &BiliFavClient,
+    mut options: ExportOptions,
+) -> Result<ExportResult, ExportError> {
     let csv_path = options.csv_path.clone();
     let encoding = options.encoding.clone();
     let timestamp = options.timestamp.take().unwrap_or_else(current_timestamp);
-    let progress_callback = options.progress_callback.clone();
-
-    let client_options = ClientOptions {
-        timeout: Duration::from_secs(options.timeout_secs),
-        cookie: options.cookie.clone(),
-        extra_headers: options.extra_headers.clone(),
-        base_url: options.base_url.clone(),
+    let callback = options.event_callback.clone();
+    let emit = |event: ExportEvent| {
+        if let Some(callback) = callback.as_ref() {
+            callback(event);
+        }
     };
-    let client = BiliFavClient::new(client_options)?;
 
-    let folder_info = client
-        .get_folder_info(media_id)
-        .await
-        .map_err(ExportError::from)?;
+    let retries = Arc::new(AtomicU64::new(0));
+    let observer_retries = Arc::clone(&retries);
+    let observer_callback = callback.clone();
+    let client = client
+        .clone()
+        .with_retry_observer(Arc::new(move |event: &RetryEvent| {
+            let retries = observer_retries.fetch_add(1, Ordering::SeqCst) + 1;
+            if let Some(callback) = observer_callback.as_ref() {
+                callback(ExportEvent::RetryScheduled {
+                    retry: event.clone(),
+                    retries,
+                });
+            }
+        }));
+
+    let cancel = options.cancel.clone();
+    let lookup = async {
+        let source = client.resolve_source(&options.fav_url).await?;
+        let folder_info = client.get_source_info(source).await?;
+        Ok::<_, FavlistError>((source, folder_info))
+    };
+    let Some(lookup) = cancellable(cancel.as_ref(), lookup).await else {
+        return Err(ExportError::Cancelled {
+            written: 0,
+            path: csv_path,
+        });
+    };
+    let (source, folder_info) = lookup?;
     let total_count = if folder_info.media_count > 0 {
         Some(folder_info.media_count as u64)
     } else {
         None
     };
-    let page_payloads = client
-        .list_videos(media_id, options.page_size)
-        .await
-        .map_err(ExportError::from)?;
-
-    let mut existing_ids = load_existing_bv_ids(&csv_path, &encoding).map_err(ExportError::from)?;
+    // CSV 只在打开时检查一次表头版本。
+    let (mut sink, migration): (Box<dyn ExportSink>, _) = if options.format == ExportFormat::Csv {
+        let sink = CsvSink::open(&csv_path, &encoding)?;
+        let migration = sink.migration().cloned();
+        (Box::new(sink), migration)
+    } else {
+        (create_sink(options.format, &csv_path, &encoding)?, None)
+    };
+    let mut existing_ids = sink.load_existing_ids().map_err(ExportError::from)?;
 
     let mut new_entries = Vec::new();
+    let mut invalid_entries = Vec::new();
+    let mut seen_entries = Vec::new();
     let mut processed_count: u64 = 0;
-    if let Some(callback) = progress_callback.as_ref() {
-        callback(ExportProgress {
-            current: processed_count,
+    emit(ExportEvent::FolderInfo {
+        folder: folder_info.clone(),
+        total: total_count,
+    });
+
+    // 逐页消费并立即落盘：中途失败时已抓取的页面不会丢失。
+    let incremental = options.incremental.clone();
+    let mut consecutive_known = 0usize;
+    let mut stopped_early = false;
+    let mut latest_fav_time: Option<i64> = None;
+    let mut pages = client.source_pages(source, options.page_size);
+    let mut page_number = 0u32;
+    let mut cancelled = false;
+    loop {
+        let Some(next) = cancellable(cancel.as_ref(), pages.next()).await else {
+            cancelled = true;
+            break;
+        };
+        let Some(page) = next else {
+            break;
+        };
+        page_number += 1;
+        let page = page.map_err(|err| {
+            ExportError::from(err).context(format!(
+                "第 {page_number} 页抓取失败（此前 {} 条新增条目已写入 {}）",
+                new_entries.len(),
+                csv_path.display()
+            ))
+        })?;
+
+        let page_start = new_entries.len();
+        emit(ExportEvent::PageFetched {
+            page: page_number,
+            items: page.medias.len(),
+            processed: processed_count.saturating_add(page.medias.len() as u64),
             total: total_count,
         });
-    }
-
-    for page in page_payloads {
         for item in page.medias {
             processed_count = processed_count.saturating_add(1);
-            if let Some(callback) = progress_callback.as_ref() {
-                callback(ExportProgress {
-                    current: processed_count,
-                    total: total_count,
-                });
+
+            if item.fav_time > 0 {
+                latest_fav_time =
+                    Some(latest_fav_time.map_or(item.fav_time, |latest| latest.max(item.fav_time)));
+            }
+            let synced_before = incremental
+                .as_ref()
+                .and_then(|inc| inc.since_fav_time)
+                .is_some_and(|since| item.fav_time > 0 && item.fav_time <= since);
+            if synced_before {
+                stopped_early = true;
             }
 
             if let Some(entry) = build_video_entry(&item, &folder_info.title, &timestamp) {
+                seen_entries.push(entry.clone());
+                if !entry.status.is_available() {
+                    match options.invalid_policy {
+                        InvalidVideoPolicy::Include => invalid_entries.push(entry.clone()),
+                        InvalidVideoPolicy::Skip => continue,
+                        InvalidVideoPolicy::Separate => {
+                            invalid_entries.push(entry);
+                            continue;
+                        }
+                    }
+                }
                 if existing_ids.insert(entry.bv_id.clone()) {
                     new_entries.push(entry);
+                    consecutive_known = 0;
+                } else {
+                    consecutive_known += 1;
+                    emit(ExportEvent::DuplicateSkipped { bvid: entry.bv_id });
                 }
             }
         }
+
+        if new_entries.len() > page_start {
+            emit(ExportEvent::WriteStarted {
+                page: page_number,
+                count: new_entries.len() - page_start,
+                path: csv_path.clone(),
+            });
+            sink.write(&new_entries[page_start..])
+                .map_err(ExportError::from)?;
+        }
+
+        if let Some(inc) = incremental.as_ref() {
+            if inc.stop_after_known > 0 && consecutive_known >= inc.stop_after_known {
+                stopped_early = true;
+            }
+            if stopped_early {
+                break;
+            }
+        }
     }
 
-    if new_entries.is_empty() {
-        ensure_csv_exists(&csv_path, &encoding)?;
-    } else {
-        write_entries(&csv_path, &encoding, &new_entries).map_err(ExportError::from)?;
+    sink.finish().map_err(ExportError::from)?;
+    if cancelled {
+        return Err(ExportError::Cancelled {
+            written: new_entries.len(),
+            path: csv_path,
+        });
     }
 
+    let retry_count = retries.load(Ordering::SeqCst);
+    emit(ExportEvent::Finished {
+        processed: processed_count,
+        new_entries: new_entries.len(),
+        retries: retry_count,
+        stopped_early,
+    });
     Ok(ExportResult {
         csv_path,
         folder_info,
         new_entries,
+        invalid_entries,
+        seen_entries,
         timestamp,
         processed_count,
         total_count,
+        retry_count,
+        stopped_early,
+        latest_fav_time,
+        migration,
     })
 }
 
+/// 令牌触发时放弃 `future` 并返回 `None`；已触发的令牌不会再轮询 `future`。
+async fn cancellable<F: Future>(
+    cancel: Option<&CancellationToken>,
+    future: F,
+) -> Option<F::Output> {
+    match cancel {
+        Some(token) => tokio::select! {
+            biased;
+            _ = token.cancelled() => None,
+            output = future => Some(output),
+        },
+        None => Some(future.await),
+    }
+}
+
+/// 列出链接指向的列表：单个收藏夹、合集或系列返回其自身，用户空间链接返回该用户创建的全部收藏夹。
+pub async fn list_source_folders(options: &ExportOptions) -> Result<Vec<FolderInfo>, ExportError> {
+    let client = BiliFavClient::new(options.client_options())?;
+    let source = client.resolve_source(&options.fav_url).await?;
+    source_folders(&client, source).await
+}
+
+async fn source_folders(
+    client: &BiliFavClient,
+    source: FavSource,
+) -> Result<Vec<FolderInfo>, ExportError> {
+    match source {
+        FavSource::UserFolders { up_mid } => {
+            client.list_created_folders(up_mid).await.map_err(|err| {
+                ExportError::from(err).context(format!("获取用户 {up_mid} 的收藏夹列表失败"))
+            })
+        }
+        source => Ok(vec![client.get_source_info(source).await?]),
+    }
+}
+
+/// 导出链接指向的全部收藏夹。单个收藏夹、合集或系列链接与 `export_favlist` 相同；
+/// 用户空间链接会逐个导出其创建的收藏夹，输出路径见 `folder_output_path`。
+pub async fn export_all_folders(
+    mut options: ExportOptions,
+) -> Result<Vec<ExportResult>, ExportError> {
+    // 解析、列出与逐个导出共用同一客户端，共享限速器与 Cookie。
+    let client = BiliFavClient::new(options.client_options())?;
+    let source = client.resolve_source(&options.fav_url).await?;
+    if source.list_id().is_some() {
+        // 短链接已解析，改用规范链接避免重复请求。
+        options.fav_url = source.canonical_url();
+        return Ok(vec![export_favlist_with_client(&client, options).await?]);
+    }
+    let folders = source_folders(&client, source).await?;
+    let mut results = Vec::with_capacity(folders.len());
+    for folder in folders {
+        let mut folder_options = options.clone();
+        folder_options.fav_url = folder_url(folder.mid, folder.media_id);
+        folder_options.csv_path = folder_output_path(&options.csv_path, folder.media_id);
+        let result = export_favlist_with_client(&client, folder_options)
+            .await
+            .map_err(|err| err.context(format!("收藏夹「{}」导出失败", folder.title)))?;
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// 解析 `fav_url` 指向的来源，支持纯数字 ID、`ml<id>`、移动端链接与短链接。
+pub async fn resolve_source(options: &ExportOptions) -> Result<FavSource, ExportError> {
+    let client = BiliFavClient::new(options.client_options())?;
+    Ok(client.resolve_source(&options.fav_url).await?)
+}
+
+/// 多收藏夹导出时每个收藏夹的输出路径：`<文件名>-<media_id>.<扩展名>`。
+pub fn folder_output_path(base: &Path, media_id: i64) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "favlist".to_string());
+    let file_name = match base.extension() {
+        Some(ext) => format!("{stem}-{media_id}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{media_id}"),
+    };
+    base.with_file_name(file_name)
+}
+
 pub fn export_favlist_blocking(options: ExportOptions) -> Result<ExportResult, ExportError> {
-    let rt = Builder::new_multi_thread()
+    blocking_runtime()?.block_on(export_favlist(options))
+}
+
+pub fn export_all_folders_blocking(
+    options: ExportOptions,
+) -> Result<Vec<ExportResult>, ExportError> {
+    blocking_runtime()?.block_on(export_all_folders(options))
+}
+
+pub fn list_source_folders_blocking(
+    options: &ExportOptions,
+) -> Result<Vec<FolderInfo>, ExportError> {
+    blocking_runtime()?.block_on(list_source_folders(options))
+}
+
+pub fn resolve_source_blocking(options: &ExportOptions) -> Result<FavSource, ExportError> {
+    blocking_runtime()?.block_on(resolve_source(options))
+}
+
+pub(crate) fn blocking_runtime() -> Result<Runtime, FavlistError> {
+    Builder::new_multi_thread()
         .enable_all()
         .build()
-        .map_err(|err| FavlistError::Other(format!("Tokio运行时初始化失败: {err}")))?;
-    rt.block_on(export_favlist(options))
+        .map_err(|err| FavlistError::Other(format!("Tokio运行时初始化失败: {err}")))
+}
+
+impl ExportOptions {
+    /// 导出时创建客户端所用的设置。
+    pub fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            timeout: Duration::from_secs(self.timeout_secs),
+            cookie: self.cookie.clone(),
+            cookie_file: self.cookie_file.clone(),
+            extra_headers: self.extra_headers.clone(),
+            base_url: self.base_url.clone(),
+            proxy: self.proxy.clone(),
+            short_link_base: self.short_link_base.clone(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            fixtures: self.fixtures.clone(),
+        }
+    }
 }
 
 fn build_video_entry(item: &VideoItem, fav_title: &str, timestamp: &str) -> Option<VideoEntry> {
@@ -150,11 +522,16 @@ fn build_video_entry(item: &VideoItem, fav_title: &str, timestamp: &str) -> Opti
         fav_title: fav_title.to_string(),
         timestamp: timestamp.to_string(),
         aid: item.id,
+        upper: item.upper.clone(),
+        duration: item.duration,
+        page_count: item.page,
+        fav_time: item.fav_time,
+        pubtime: item.pubtime,
+        ctime: item.ctime,
+        cover: item.cover.trim().to_string(),
+        intro: item.intro.trim().to_string(),
+        attr: item.attr,
+        stats: item.cnt_info.clone(),
+        status: item.status(),
     })
 }
-
-fn ensure_csv_exists(path: &Path, encoding: &str) -> Result<(), ExportError> {
-    write_entries(path, encoding, &[])
-        .map(|_| ())
-        .map_err(ExportError::from)
-}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::errors::FavlistError;
+
+/// 录制/回放 API 请求，便于离线复现问题与编写测试。
+///
+/// 每个请求按接口路径与查询参数保存为目录下的一个 JSON 文件，与 `base_url` 无关，
+/// 因此从真实接口录制的数据可以直接在任意地址回放。只记录 API 响应，不记录 Cookie 等请求头；
+/// 短链接解析与扫码登录不经过此机制。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// 正常请求，并把请求与响应写入目录（同名文件会被覆盖）。
+    Record(PathBuf),
+    /// 不发出网络请求，从目录读取录制的响应；缺少对应文件时报错。
+    Replay(PathBuf),
+}
+
+impl FixtureMode {
+    pub fn dir(&self) -> &Path {
+        match self {
+            FixtureMode::Record(dir) | FixtureMode::Replay(dir) => dir,
+        }
+    }
+}
+
+/// 单个请求的录制内容。`body` 能解析为 JSON 时按原结构保存，否则保存为字符串。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub path: String,
+    #[serde(default)]
+    pub query: Vec<(String, String)>,
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+impl Fixture {
+    pub fn new(url: &str, params: &[(&str, String)], status: u16, body: &[u8]) -> Self {
+        let body = serde_json::from_slice(body).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(body).into_owned())
+        });
+        Self {
+            path: request_path(url),
+            query: params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+            status,
+            body,
+        }
+    }
+
+    pub fn body_bytes(&self) -> Vec<u8> {
+        match &self.body {
+            serde_json::Value::String(text) => text.clone().into_bytes(),
+            other => other.to_string().into_bytes(),
+        }
+    }
+}
+
+/// 录制文件名，如 `x_v3_fav_resource_list__media_id_88_pn_1.json`。
+pub fn fixture_file_name(url: &str, params: &[(&str, String)]) -> String {
+    let mut name = slug(request_path(url).trim_start_matches('/'));
+    if !params.is_empty() {
+        name.push_str("__");
+        let query: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{key}_{value}"))
+            .collect();
+        name.push_str(&slug(&query.join("_")));
+    }
+    name.push_str(".json");
+    name
+}
+
+pub(crate) fn save_fixture(dir: &Path, fixture: &Fixture) -> Result<(), FavlistError> {
+    fs::create_dir_all(dir)?;
+    let params: Vec<(&str, String)> = fixture
+        .query
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.clone()))
+        .collect();
+    let path = dir.join(fixture_file_name(&fixture.path, &params));
+    let json = serde_json::to_string_pretty(fixture)
+        .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+pub(crate) fn load_fixture(
+    dir: &Path,
+    url: &str,
+    params: &[(&str, String)],
+) -> Result<Fixture, FavlistError> {
+    let path = dir.join(fixture_file_name(url, params));
+    let content = fs::read_to_string(&path)
+        .map_err(|err| FavlistError::Fixture(format!("缺少回放数据 {}: {err}", path.display())))?;
+    serde_json::from_str(&content)
+        .map_err(|err| FavlistError::Fixture(format!("回放数据 {} 无效: {err}", path.display())))
+}
+
+/// 完整 URL 取其路径，已是路径时原样返回。
+fn request_path(url: &str) -> String {
+    Url::parse(url)
+        .map(|parsed| parsed.path().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+fn slug(text: &str) -> String {
+    text.chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect()
+}
@@ -7,22 +7,47 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use walkdir::WalkDir;
 
+use crate::bvid::{canonical_video_id, normalize_video_id};
 use crate::csv_utils::CsvRow;
+use crate::models::{InvalidVideoPolicy, VideoStatus};
 
 lazy_static! {
-    static ref BV_PATTERN: Regex = Regex::new(r"(BV[0-9A-Za-z]{10})").expect("BV正则");
+    /// `av` 号只认小写、至少 3 位数字且前后都与字母数字断开，
+    /// 避免误匹配 `Java123` 或 `[AV1]`、`.av1.` 之类的编码标签。后边界在匹配后检查。
+    static ref VIDEO_ID_PATTERN: Regex =
+        Regex::new(r"(BV[0-9A-Za-z]{10})|(?:^|[^0-9A-Za-z])(av[0-9]{3,})").expect("视频ID正则");
 }
 
+/// 下载目录下存放已取消收藏视频的归档子目录，扫描本地文件时跳过。
+pub const REMOVED_ARCHIVE_DIR: &str = "_removed";
+
+/// 提取文本中的 BV 号与 av 号，统一转换为 BV 形式。
 pub fn extract_bvids(text: &str) -> HashSet<String> {
-    BV_PATTERN
-        .find_iter(text)
-        .map(|m| m.as_str().to_string())
+    VIDEO_ID_PATTERN
+        .captures_iter(text)
+        .filter_map(|caps| {
+            caps.get(1).or_else(|| {
+                caps.get(2).filter(|m| {
+                    !text[m.end()..]
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphanumeric())
+                })
+            })
+        })
+        .filter_map(|m| normalize_video_id(m.as_str()))
         .collect()
 }
 
+/// 递归扫描目录中文件名含视频 ID 的文件，顶层的 `REMOVED_ARCHIVE_DIR` 归档目录不计入。
 pub fn scan_directory_bvids(directory: &Path) -> io::Result<HashMap<String, Vec<PathBuf>>> {
     let mut mapping: HashMap<String, Vec<PathBuf>> = HashMap::new();
-    for entry in WalkDir::new(directory).into_iter().filter_map(Result::ok) {
+    let walker = WalkDir::new(directory).into_iter().filter_entry(|entry| {
+        !(entry.depth() == 1
+            && entry.file_type().is_dir()
+            && entry.file_name() == REMOVED_ARCHIVE_DIR)
+    });
+    for entry in walker.filter_map(Result::ok) {
         if entry.file_type().is_file() {
             let path = entry.path().to_path_buf();
             let name = entry.file_name().to_string_lossy();
@@ -57,12 +82,13 @@ pub fn write_inventory_file(
     Ok(inventory_path)
 }
 
-fn extract_bvid_from_row(row: &CsvRow) -> Option<String> {
+/// CSV 行中的视频 ID（兼容旧版中文列名），`av` 号会转换为 BV 形式。
+pub fn row_bvid(row: &CsvRow) -> Option<String> {
     for key in ["bv_id", "BV号", "视频BV号"] {
         if let Some(value) = row.get(key) {
             let trimmed = value.trim();
             if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
+                return Some(canonical_video_id(trimmed));
             }
         }
     }
@@ -70,11 +96,11 @@ fn extract_bvid_from_row(row: &CsvRow) -> Option<String> {
 }
 
 pub fn diff_new_entries(old_rows: &[CsvRow], new_rows: &[CsvRow]) -> Vec<CsvRow> {
-    let old_set: HashSet<String> = old_rows.iter().filter_map(extract_bvid_from_row).collect();
+    let old_set: HashSet<String> = old_rows.iter().filter_map(row_bvid).collect();
     new_rows
         .iter()
         .filter(|row| {
-            if let Some(bvid) = extract_bvid_from_row(row) {
+            if let Some(bvid) = row_bvid(row) {
                 !old_set.contains(&bvid)
             } else {
                 false
@@ -84,12 +110,93 @@ pub fn diff_new_entries(old_rows: &[CsvRow], new_rows: &[CsvRow]) -> Vec<CsvRow>
         .collect()
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleChange {
+    pub bv_id: String,
+    pub old_title: String,
+    pub new_title: String,
+}
+
+/// 两次抓取之间的完整变更：新增、取消收藏、标题变化与新失效。
+#[derive(Debug, Clone, Default)]
+pub struct ChangeSet {
+    pub added: Vec<CsvRow>,
+    /// 旧列表中存在、新列表中已不存在的条目（取自旧行）。
+    pub removed: Vec<CsvRow>,
+    pub title_changed: Vec<TitleChange>,
+    /// 旧列表中可用、新列表中已失效的条目（取自新行）。
+    pub became_invalid: Vec<CsvRow>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.title_changed.is_empty()
+            && self.became_invalid.is_empty()
+    }
+}
+
+pub fn diff_changes(old_rows: &[CsvRow], new_rows: &[CsvRow]) -> ChangeSet {
+    let old_map: HashMap<String, &CsvRow> = old_rows
+        .iter()
+        .filter_map(|row| row_bvid(row).map(|bvid| (bvid, row)))
+        .collect();
+    let mut new_ids = HashSet::new();
+    let mut changes = ChangeSet::default();
+    for row in new_rows {
+        let Some(bvid) = row_bvid(row) else {
+            continue;
+        };
+        if !new_ids.insert(bvid.clone()) {
+            continue;
+        }
+        let Some(old_row) = old_map.get(&bvid) else {
+            changes.added.push(row.clone());
+            continue;
+        };
+        let old_status = row_status(old_row);
+        let new_status = row_status(row);
+        if old_status.is_available() && !new_status.is_available() {
+            // 失效后标题会变为“已失效视频”，不再额外记为标题变化。
+            changes.became_invalid.push(row.clone());
+            continue;
+        }
+        let old_title = row_title(old_row);
+        let new_title = row_title(row);
+        if new_status.is_available() && !old_title.is_empty() && old_title != new_title {
+            changes.title_changed.push(TitleChange {
+                bv_id: bvid,
+                old_title: old_title.to_string(),
+                new_title: new_title.to_string(),
+            });
+        }
+    }
+    let mut removed_ids = HashSet::new();
+    changes.removed = old_rows
+        .iter()
+        .filter(|row| {
+            row_bvid(row).is_some_and(|bvid| !new_ids.contains(&bvid) && removed_ids.insert(bvid))
+        })
+        .cloned()
+        .collect();
+    changes
+}
+
+fn row_title(row: &CsvRow) -> &str {
+    ["title", "标题", "视频标题"]
+        .iter()
+        .find_map(|key| row.get(*key))
+        .map(|value| value.trim())
+        .unwrap_or_default()
+}
+
 pub fn find_missing_videos(csv_rows: &[CsvRow], existing_bvids: &[String]) -> Vec<CsvRow> {
     let existing: HashSet<&str> = existing_bvids.iter().map(|s| s.as_str()).collect();
     csv_rows
         .iter()
         .filter(|row| {
-            if let Some(bvid) = extract_bvid_from_row(row) {
+            if let Some(bvid) = row_bvid(row) {
                 !existing.contains(bvid.as_str())
             } else {
                 false
@@ -98,3 +205,45 @@ pub fn find_missing_videos(csv_rows: &[CsvRow], existing_bvids: &[String]) -> Ve
         .cloned()
         .collect()
 }
+
+/// 读取 CSV 行中记录的状态；旧文件没有 `status` 列时按可用处理。
+pub fn row_status(row: &CsvRow) -> VideoStatus {
+    let status = row
+        .get("status")
+        .map(|value| VideoStatus::parse(value))
+        .unwrap_or_default();
+    if status.is_available() {
+        let title = row.get("title").map(String::as_str).unwrap_or_default();
+        VideoStatus::classify(0, title)
+    } else {
+        status
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MissingVideos {
+    /// 本地缺失且仍可下载的条目。
+    pub missing: Vec<CsvRow>,
+    /// 本地缺失但已失效的条目（仅 `InvalidVideoPolicy::Separate` 时填充）。
+    pub invalid: Vec<CsvRow>,
+}
+
+pub fn classify_missing_videos(
+    csv_rows: &[CsvRow],
+    existing_bvids: &[String],
+    policy: InvalidVideoPolicy,
+) -> MissingVideos {
+    let mut result = MissingVideos::default();
+    for row in find_missing_videos(csv_rows, existing_bvids) {
+        if row_status(&row).is_available() {
+            result.missing.push(row);
+            continue;
+        }
+        match policy {
+            InvalidVideoPolicy::Include => result.missing.push(row),
+            InvalidVideoPolicy::Skip => {}
+            InvalidVideoPolicy::Separate => result.invalid.push(row),
+        }
+    }
+    result
+}
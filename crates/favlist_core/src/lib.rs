@@ -1,21 +1,61 @@
+pub mod bvid;
 pub mod client;
+pub mod cookies;
 pub mod csv_utils;
 pub mod errors;
 pub mod export;
+pub mod fixtures;
 pub mod inventory;
+pub mod library;
+pub mod login;
 pub mod models;
+pub mod proxy;
+pub mod rate_limit;
+pub mod retry;
+pub mod sink;
+pub mod source;
+pub mod sync;
 pub mod timestamp;
 
+pub use bvid::{av_to_bv, bv_to_av, canonical_video_id, normalize_video_id};
 pub use client::{BiliFavClient, ClientOptions, DEFAULT_HEADERS};
-pub use csv_utils::{load_existing_bv_ids, read_csv_rows, write_entries, CsvRow, FIELDNAMES};
+pub use cookies::{load_cookie_file, CookieFileFormat};
+pub use csv_utils::{
+    detect_schema, load_existing_bv_ids, migrate_csv, read_csv_rows, schema_columns, write_entries,
+    CsvMigration, CsvRow, CsvSchema, CSV_SCHEMA_VERSION, FIELDNAMES,
+};
 pub use errors::{ExportError, FavlistError};
 pub use export::{
-    export_favlist, export_favlist_blocking, ExportOptions, ExportProgress, ExportResult,
-    ProgressCallback,
+    export_all_folders, export_all_folders_blocking, export_favlist, export_favlist_blocking,
+    export_favlist_with_client, folder_output_path, list_source_folders,
+    list_source_folders_blocking, resolve_source, resolve_source_blocking, ExportCallback,
+    ExportEvent, ExportOptions, ExportResult, IncrementalOptions,
 };
+pub use fixtures::{fixture_file_name, Fixture, FixtureMode};
 pub use inventory::{
-    diff_new_entries, extract_bvids, find_missing_videos, scan_directory_bvids,
-    write_inventory_file,
+    classify_missing_videos, diff_changes, diff_new_entries, extract_bvids, find_missing_videos,
+    row_bvid, row_status, scan_directory_bvids, write_inventory_file, ChangeSet, MissingVideos,
+    TitleChange, REMOVED_ARCHIVE_DIR,
+};
+pub use library::{DownloadState, FolderSync, Library, LibraryVideo};
+pub use login::{
+    login_with_qr_blocking, refresh_credentials_file_blocking, render_qr, LoginCredentials,
+    LoginOptions, QrLogin, QrPollStatus, QrTicket,
+};
+pub use models::{
+    FolderInfo, InvalidVideoPolicy, Uploader, VideoEntry, VideoItem, VideoStats, VideoStatus,
+};
+pub use proxy::ProxyConfig;
+pub use rate_limit::RateLimiter;
+pub use retry::{RetryEvent, RetryObserver, RetryPolicy, RISK_CONTROL_CODES};
+pub use sink::{create_sink, CsvSink, ExportFormat, ExportSink, JsonSink, JsonlSink, SqliteSink};
+pub use source::{
+    folder_url, parse_fav_source, parse_source_ref, FavSource, SourceRef, HISTORY_LIST_ID,
+    WATCH_LATER_LIST_ID,
+};
+pub use sync::{
+    sync_folders, sync_folders_blocking, SyncCallback, SyncEvent, SyncJob, SyncOptions,
+    SyncOutcome, SyncReport, DEFAULT_SYNC_CONCURRENCY,
 };
-pub use models::{FolderInfo, VideoEntry};
 pub use timestamp::{current_timestamp, parse_media_id};
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::csv_utils::CsvRow;
+use crate::errors::FavlistError;
+use crate::inventory::{row_bvid, row_status};
+use crate::models::{VideoEntry, VideoStatus};
+use crate::timestamp::current_timestamp;
+
+const LIBRARY_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS videos (
+    bv_id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    status TEXT NOT NULL,
+    aid INTEGER,
+    upper_mid INTEGER NOT NULL DEFAULT 0,
+    upper_name TEXT NOT NULL DEFAULT '',
+    duration INTEGER NOT NULL DEFAULT 0,
+    page_count INTEGER NOT NULL DEFAULT 0,
+    pubtime INTEGER NOT NULL DEFAULT 0,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    download_state TEXT NOT NULL DEFAULT 'pending'
+);
+CREATE TABLE IF NOT EXISTS folders (
+    media_id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    last_synced TEXT
+);
+CREATE TABLE IF NOT EXISTS memberships (
+    bv_id TEXT NOT NULL,
+    media_id INTEGER NOT NULL,
+    fav_time INTEGER NOT NULL DEFAULT 0,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    PRIMARY KEY (bv_id, media_id)
+);
+CREATE TABLE IF NOT EXISTS local_files (
+    root TEXT NOT NULL,
+    path TEXT NOT NULL,
+    bv_id TEXT NOT NULL,
+    PRIMARY KEY (root, path)
+);
+CREATE INDEX IF NOT EXISTS idx_local_files_bv_id ON local_files (bv_id);
+";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DownloadState {
+    #[default]
+    Pending,
+    Downloaded,
+    Failed,
+}
+
+impl DownloadState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DownloadState::Pending => "pending",
+            DownloadState::Downloaded => "downloaded",
+            DownloadState::Failed => "failed",
+        }
+    }
+
+    /// 无法识别的值按待下载处理。
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "downloaded" => DownloadState::Downloaded,
+            "failed" => DownloadState::Failed,
+            _ => DownloadState::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryVideo {
+    pub bv_id: String,
+    pub title: String,
+    pub status: VideoStatus,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub download_state: DownloadState,
+    /// 所属收藏夹的 media_id。
+    pub folders: Vec<i64>,
+    pub local_paths: Vec<PathBuf>,
+}
+
+impl LibraryVideo {
+    /// 本地没有文件且视频仍可下载。
+    pub fn is_missing(&self) -> bool {
+        self.status.is_available() && self.local_paths.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FolderSync {
+    /// 此前未出现在该收藏夹中的 BV 号，按写入顺序排列。
+    pub added: Vec<String>,
+    pub seen: usize,
+}
+
+/// 本地视频库：跨收藏夹记录视频、所属收藏夹、首末次出现时间、下载状态与本地文件。
+pub struct Library {
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl Library {
+    pub fn open(path: &Path) -> Result<Self, FavlistError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(LIBRARY_SCHEMA)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            conn,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn has_folder(&self, media_id: i64) -> Result<bool, FavlistError> {
+        let found = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM folders WHERE media_id = ?1",
+                params![media_id],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// 记录一次收藏夹抓取结果。已存在的视频只刷新元数据与 `last_seen`；失效条目保留原标题。
+    pub fn record_folder(
+        &mut self,
+        media_id: i64,
+        folder_title: &str,
+        entries: &[VideoEntry],
+        seen_at: &str,
+    ) -> Result<FolderSync, FavlistError> {
+        self.record_entries(media_id, folder_title, seen_at, entries, |_| seen_at)
+    }
+
+    /// 在同一事务中写入条目，`seen_at` 给出每个条目的出现时间。
+    fn record_entries<'a>(
+        &mut self,
+        media_id: i64,
+        folder_title: &str,
+        last_synced: &str,
+        entries: &'a [VideoEntry],
+        seen_at: impl Fn(&'a VideoEntry) -> &'a str,
+    ) -> Result<FolderSync, FavlistError> {
+        let tx = self.conn.transaction()?;
+        let mut sync = FolderSync::default();
+        tx.execute(
+            "INSERT INTO folders (media_id, title, last_synced) VALUES (?1, ?2, ?3)
+             ON CONFLICT(media_id) DO UPDATE SET title = excluded.title, last_synced = excluded.last_synced",
+            params![media_id, folder_title, last_synced],
+        )?;
+        {
+            let mut upsert_video = tx.prepare(
+                "INSERT INTO videos (
+                    bv_id, title, status, aid, upper_mid, upper_name, duration, page_count,
+                    pubtime, first_seen, last_seen
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
+                ON CONFLICT(bv_id) DO UPDATE SET
+                    title = CASE WHEN excluded.status = 'available' THEN excluded.title ELSE videos.title END,
+                    status = excluded.status,
+                    aid = COALESCE(excluded.aid, videos.aid),
+                    upper_mid = excluded.upper_mid,
+                    upper_name = excluded.upper_name,
+                    duration = excluded.duration,
+                    page_count = excluded.page_count,
+                    pubtime = excluded.pubtime,
+                    first_seen = MIN(videos.first_seen, excluded.first_seen),
+                    last_seen = MAX(videos.last_seen, excluded.last_seen)",
+            )?;
+            let mut insert_membership = tx.prepare(
+                "INSERT OR IGNORE INTO memberships (bv_id, media_id, fav_time, first_seen, last_seen)
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+            )?;
+            let mut touch_membership = tx.prepare(
+                "UPDATE memberships SET
+                    first_seen = MIN(first_seen, ?3), last_seen = MAX(last_seen, ?3), fav_time = MAX(fav_time, ?4)
+                 WHERE bv_id = ?1 AND media_id = ?2",
+            )?;
+            let mut seen = HashSet::new();
+            for entry in entries {
+                if !seen.insert(entry.bv_id.as_str()) {
+                    continue;
+                }
+                let seen_at = seen_at(entry);
+                upsert_video.execute(params![
+                    entry.bv_id,
+                    entry.title,
+                    entry.status.as_str(),
+                    entry.aid,
+                    entry.upper.mid,
+                    entry.upper.name,
+                    entry.duration,
+                    entry.page_count,
+                    entry.pubtime,
+                    seen_at,
+                ])?;
+                if insert_membership.execute(params![
+                    entry.bv_id,
+                    media_id,
+                    entry.fav_time,
+                    seen_at
+                ])? > 0
+                {
+                    sync.added.push(entry.bv_id.clone());
+                } else {
+                    touch_membership.execute(params![
+                        entry.bv_id,
+                        media_id,
+                        seen_at,
+                        entry.fav_time
+                    ])?;
+                }
+            }
+            sync.seen = seen.len();
+        }
+        tx.commit()?;
+        Ok(sync)
+    }
+
+    /// 导入旧 CSV 中的历史记录，首末次出现时间取行内的抓取时间（缺失时取当前时间），
+    /// 收藏夹的 `last_synced` 取最新一行的时间。全部行在同一事务中写入。
+    pub fn import_csv_rows(
+        &mut self,
+        media_id: i64,
+        folder_title: &str,
+        rows: &[CsvRow],
+    ) -> Result<FolderSync, FavlistError> {
+        let now = current_timestamp();
+        let mut entries = Vec::with_capacity(rows.len());
+        for row in rows {
+            let Some(bv_id) = row_bvid(row) else {
+                continue;
+            };
+            let field = |key: &str| row.get(key).map(|value| value.trim()).unwrap_or_default();
+            let number = |key: &str| field(key).parse::<i64>().unwrap_or_default();
+            let mut entry = VideoEntry {
+                bv_id,
+                title: field("title").to_string(),
+                fav_title: folder_title.to_string(),
+                timestamp: field("timestamp").to_string(),
+                aid: field("aid").parse().ok(),
+                duration: number("duration"),
+                page_count: number("page_count"),
+                fav_time: number("fav_time"),
+                pubtime: number("pubtime"),
+                status: row_status(row),
+                ..Default::default()
+            };
+            entry.upper.mid = number("upper_mid");
+            entry.upper.name = field("upper_name").to_string();
+            if entry.timestamp.is_empty() {
+                entry.timestamp = now.clone();
+            }
+            entries.push(entry);
+        }
+        // 时间戳格式固定，按字符串比较即按时间先后。
+        let last_synced = entries
+            .iter()
+            .map(|entry| entry.timestamp.as_str())
+            .max()
+            .unwrap_or(&now)
+            .to_string();
+        self.record_entries(media_id, folder_title, &last_synced, &entries, |entry| {
+            entry.timestamp.as_str()
+        })
+    }
+
+    /// 用一次目录扫描结果替换 `root` 下的本地文件记录，并据此刷新下载状态。
+    pub fn record_local_files(
+        &mut self,
+        root: &Path,
+        mapping: &HashMap<String, Vec<PathBuf>>,
+    ) -> Result<usize, FavlistError> {
+        let root_key = root.to_string_lossy().to_string();
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM local_files WHERE root = ?1", params![root_key])?;
+        let mut recorded = 0usize;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO local_files (root, path, bv_id) VALUES (?1, ?2, ?3)",
+            )?;
+            for (bv_id, paths) in mapping {
+                for path in paths {
+                    recorded += insert.execute(params![
+                        root_key,
+                        path.to_string_lossy().to_string(),
+                        bv_id
+                    ])?;
+                }
+            }
+        }
+        tx.execute_batch(
+            "UPDATE videos SET download_state = 'downloaded'
+             WHERE bv_id IN (SELECT bv_id FROM local_files);
+             UPDATE videos SET download_state = 'pending'
+             WHERE download_state = 'downloaded'
+               AND bv_id NOT IN (SELECT bv_id FROM local_files);",
+        )?;
+        tx.commit()?;
+        Ok(recorded)
+    }
+
+    /// 移除收藏夹成员关系，返回实际移除的数量；视频本身的记录保留。
+    pub fn remove_from_folder<S: AsRef<str>>(
+        &mut self,
+        media_id: i64,
+        bv_ids: &[S],
+    ) -> Result<usize, FavlistError> {
+        let tx = self.conn.transaction()?;
+        let mut removed = 0usize;
+        {
+            let mut stmt =
+                tx.prepare("DELETE FROM memberships WHERE bv_id = ?1 AND media_id = ?2")?;
+            for bv_id in bv_ids {
+                removed += stmt.execute(params![bv_id.as_ref(), media_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    pub fn set_download_state(
+        &self,
+        bv_id: &str,
+        state: DownloadState,
+    ) -> Result<(), FavlistError> {
+        self.conn.execute(
+            "UPDATE videos SET download_state = ?2 WHERE bv_id = ?1",
+            params![bv_id, state.as_str()],
+        )?;
+        Ok(())
+    }
+
+    pub fn video(&self, bv_id: &str) -> Result<Option<LibraryVideo>, FavlistError> {
+        let video = self
+            .conn
+            .query_row(
+                "SELECT bv_id, title, status, first_seen, last_seen, download_state
+                 FROM videos WHERE bv_id = ?1",
+                params![bv_id],
+                read_video_row,
+            )
+            .optional()?;
+        video.map(|video| self.hydrate(video)).transpose()
+    }
+
+    /// 按收藏时间倒序返回收藏夹中的全部视频。
+    pub fn folder_videos(&self, media_id: i64) -> Result<Vec<LibraryVideo>, FavlistError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT v.bv_id, v.title, v.status, v.first_seen, v.last_seen, v.download_state
+             FROM videos v JOIN memberships m ON m.bv_id = v.bv_id
+             WHERE m.media_id = ?1
+             ORDER BY m.fav_time DESC, m.first_seen DESC, v.bv_id",
+        )?;
+        let videos = stmt
+            .query_map(params![media_id], read_video_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        videos
+            .into_iter()
+            .map(|video| self.hydrate(video))
+            .collect()
+    }
+
+    fn hydrate(&self, mut video: LibraryVideo) -> Result<LibraryVideo, FavlistError> {
+        let mut folders = self
+            .conn
+            .prepare("SELECT media_id FROM memberships WHERE bv_id = ?1 ORDER BY media_id")?;
+        video.folders = folders
+            .query_map(params![video.bv_id], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut paths = self
+            .conn
+            .prepare("SELECT path FROM local_files WHERE bv_id = ?1 ORDER BY path")?;
+        video.local_paths = paths
+            .query_map(params![video.bv_id], |row| row.get::<_, String>(0))?
+            .map(|path| path.map(PathBuf::from))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(video)
+    }
+}
+
+fn read_video_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<LibraryVideo> {
+    Ok(LibraryVideo {
+        bv_id: row.get(0)?,
+        title: row.get(1)?,
+        status: VideoStatus::parse(&row.get::<_, String>(2)?),
+        first_seen: row.get(3)?,
+        last_seen: row.get(4)?,
+        download_state: DownloadState::parse(&row.get::<_, String>(5)?),
+        folders: Vec::new(),
+        local_paths: Vec::new(),
+    })
+}
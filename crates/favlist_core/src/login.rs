@@ -0,0 +1,490 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+use reqwest::{Client, RequestBuilder};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::sha2::Sha256;
+use rsa::{Oaep, RsaPublicKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::client::DEFAULT_HEADERS;
+use crate::errors::FavlistError;
+use crate::export::blocking_runtime;
+use crate::models::{
+    ApiResponse, CookieInfoPayload, CookieRefreshPayload, QrGeneratePayload, QrPollPayload,
+};
+use crate::proxy::ProxyConfig;
+
+const DEFAULT_PASSPORT_BASE: &str = "https://passport.bilibili.com";
+const DEFAULT_WWW_BASE: &str = "https://www.bilibili.com";
+const QR_GENERATE_PATH: &str = "/x/passport-login/web/qrcode/generate";
+const QR_POLL_PATH: &str = "/x/passport-login/web/qrcode/poll";
+const COOKIE_INFO_PATH: &str = "/x/passport-login/web/cookie/info";
+const COOKIE_REFRESH_PATH: &str = "/x/passport-login/web/cookie/refresh";
+const CONFIRM_REFRESH_PATH: &str = "/x/passport-login/web/confirm/refresh";
+const CORRESPOND_PATH: &str = "/correspond/1";
+
+const QR_SUCCESS: i32 = 0;
+const QR_EXPIRED: i32 = 86038;
+const QR_SCANNED: i32 = 86090;
+const QR_WAITING: i32 = 86101;
+
+/// 生成 `correspondPath` 所用的 RSA 公钥，来自主站前端。
+const CORRESPOND_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDLgd2OAkcGVtoE3ThUREbio0Eg
+Uc/prcajMKXvkCKFCWhJYJcLkcM2DKKcSeFpD/j6Boy538YXnR6VhcuUJOhH2x71
+nzPjfdTcqMz7djHum0qSZA0AyCBDABUqCrfNgCiJ00Ra7GmRj+YCK1NJEuewlb40
+JNrRuoEUXpabUzGB8QIDAQAB
+-----END PUBLIC KEY-----";
+
+/// 扫码回调链接与 Set-Cookie 中需要保存的 Cookie。
+const CREDENTIAL_COOKIES: [&str; 5] = [
+    "SESSDATA",
+    "bili_jct",
+    "DedeUserID",
+    "DedeUserID__ckMd5",
+    "sid",
+];
+
+#[derive(Debug, Clone)]
+pub struct LoginOptions {
+    pub timeout: Duration,
+    /// 登录接口地址，默认 `https://passport.bilibili.com`。
+    pub passport_base: Option<String>,
+    /// 主站地址，刷新 Cookie 时从其 `correspond` 页面读取 `refresh_csrf`。
+    pub www_base: Option<String>,
+    pub proxy: Option<ProxyConfig>,
+    pub poll_interval: Duration,
+    /// 距离过期不足该时长时主动刷新。
+    pub refresh_margin: Duration,
+}
+
+impl Default for LoginOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            passport_base: None,
+            www_base: None,
+            proxy: None,
+            poll_interval: Duration::from_secs(2),
+            refresh_margin: Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+/// 扫码登录得到的凭据，以 JSON 保存，可直接作为 `cookie_file` 使用。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginCredentials {
+    pub cookies: BTreeMap<String, String>,
+    pub refresh_token: String,
+    /// Cookie 过期时间（Unix 秒）。
+    pub expires_at: Option<i64>,
+    pub updated_at: i64,
+}
+
+impl LoginCredentials {
+    pub fn cookie_header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn csrf(&self) -> Option<&str> {
+        self.cookies.get("bili_jct").map(String::as_str)
+    }
+
+    pub fn needs_refresh(&self, now: i64, margin: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at - now <= margin.as_secs() as i64)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, FavlistError> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|err| FavlistError::InvalidJson(err.to_string()))
+    }
+
+    /// 经临时文件整体替换，避免写入中断留下损坏的凭据。
+    pub fn save(&self, path: &Path) -> Result<(), FavlistError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    fn merge_cookies<'a>(&mut self, pairs: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        for (name, value) in pairs {
+            if name == "Expires" {
+                if let Ok(expires_at) = value.parse() {
+                    self.expires_at = Some(expires_at);
+                }
+            } else if CREDENTIAL_COOKIES.contains(&name) {
+                self.cookies.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+}
+
+/// 待扫描的二维码：`url` 用于生成二维码，`qrcode_key` 用于轮询。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrTicket {
+    pub url: String,
+    pub qrcode_key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QrPollStatus {
+    Waiting,
+    /// 已扫码，等待在手机上确认。
+    Scanned,
+    Expired,
+    Confirmed(LoginCredentials),
+}
+
+pub struct QrLogin {
+    client: Client,
+    options: LoginOptions,
+    passport_base: String,
+    www_base: String,
+}
+
+impl QrLogin {
+    pub fn new(options: LoginOptions) -> Result<Self, FavlistError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in DEFAULT_HEADERS.iter() {
+            headers.insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+        let mut builder = Client::builder()
+            .timeout(options.timeout)
+            .default_headers(headers);
+        if let Some(proxy) = options.proxy.as_ref() {
+            builder = builder.proxy(proxy.to_proxy()?);
+        }
+        let client = builder.build().map_err(FavlistError::Request)?;
+        let base = |value: &Option<String>, default: &str| {
+            value
+                .as_deref()
+                .unwrap_or(default)
+                .trim_end_matches('/')
+                .to_string()
+        };
+        Ok(Self {
+            client,
+            passport_base: base(&options.passport_base, DEFAULT_PASSPORT_BASE),
+            www_base: base(&options.www_base, DEFAULT_WWW_BASE),
+            options,
+        })
+    }
+
+    pub async fn generate(&self) -> Result<QrTicket, FavlistError> {
+        let request = self
+            .client
+            .get(format!("{}{QR_GENERATE_PATH}", self.passport_base));
+        let (payload, _): (QrGeneratePayload, _) = send(request).await?;
+        Ok(QrTicket {
+            url: payload.url,
+            qrcode_key: payload.qrcode_key,
+        })
+    }
+
+    pub async fn poll(&self, ticket: &QrTicket) -> Result<QrPollStatus, FavlistError> {
+        let request = self
+            .client
+            .get(format!("{}{QR_POLL_PATH}", self.passport_base))
+            .query(&[("qrcode_key", ticket.qrcode_key.as_str())]);
+        let (payload, set_cookies): (QrPollPayload, _) = send(request).await?;
+        match payload.code {
+            QR_WAITING => Ok(QrPollStatus::Waiting),
+            QR_SCANNED => Ok(QrPollStatus::Scanned),
+            QR_EXPIRED => Ok(QrPollStatus::Expired),
+            QR_SUCCESS => {
+                let mut credentials = LoginCredentials {
+                    refresh_token: payload.refresh_token,
+                    updated_at: chrono::Utc::now().timestamp(),
+                    ..Default::default()
+                };
+                // 回调链接的查询串与 Set-Cookie 都带有 Cookie，以后者为准。
+                if let Ok(url) = Url::parse(&payload.url) {
+                    let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+                    credentials.merge_cookies(pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+                }
+                credentials
+                    .merge_cookies(set_cookies.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+                if !credentials.cookies.contains_key("SESSDATA") {
+                    return Err(FavlistError::Login("登录成功但未返回SESSDATA".to_string()));
+                }
+                Ok(QrPollStatus::Confirmed(credentials))
+            }
+            code => Err(FavlistError::Login(format!(
+                "未知的扫码状态(code={code}, message={})",
+                payload.message
+            ))),
+        }
+    }
+
+    /// 按 `poll_interval` 轮询直到确认登录；二维码过期时返回错误。
+    pub async fn wait_for_login(
+        &self,
+        ticket: &QrTicket,
+        mut on_status: impl FnMut(&QrPollStatus),
+    ) -> Result<LoginCredentials, FavlistError> {
+        loop {
+            let status = self.poll(ticket).await?;
+            on_status(&status);
+            match status {
+                QrPollStatus::Confirmed(credentials) => return Ok(credentials),
+                QrPollStatus::Expired => {
+                    return Err(FavlistError::Login("二维码已过期，请重新生成".to_string()))
+                }
+                QrPollStatus::Waiting | QrPollStatus::Scanned => {
+                    tokio::time::sleep(self.options.poll_interval).await;
+                }
+            }
+        }
+    }
+
+    /// 临近过期或服务端要求刷新时刷新凭据；无需刷新返回 `None`。
+    pub async fn refresh_if_needed(
+        &self,
+        credentials: &LoginCredentials,
+    ) -> Result<Option<LoginCredentials>, FavlistError> {
+        let now = chrono::Utc::now().timestamp();
+        if !credentials.needs_refresh(now, self.options.refresh_margin) {
+            let request = self
+                .with_cookies(
+                    self.client
+                        .get(format!("{}{COOKIE_INFO_PATH}", self.passport_base)),
+                    credentials,
+                )
+                .query(&[("csrf", credentials.csrf().unwrap_or_default())]);
+            let (info, _): (CookieInfoPayload, _) = send(request).await?;
+            if !info.refresh {
+                return Ok(None);
+            }
+        }
+        self.refresh(credentials).await.map(Some)
+    }
+
+    /// 按主站流程刷新：取得 `refresh_csrf`，换取新 Cookie，再确认作废旧的 `refresh_token`。
+    pub async fn refresh(
+        &self,
+        credentials: &LoginCredentials,
+    ) -> Result<LoginCredentials, FavlistError> {
+        let csrf = credentials
+            .csrf()
+            .ok_or_else(|| FavlistError::Login("凭据缺少bili_jct，无法刷新".to_string()))?;
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let refresh_csrf = self.refresh_csrf(credentials, now_ms).await?;
+
+        let request = self
+            .with_cookies(
+                self.client
+                    .post(format!("{}{COOKIE_REFRESH_PATH}", self.passport_base)),
+                credentials,
+            )
+            .form(&[
+                ("csrf", csrf),
+                ("refresh_csrf", refresh_csrf.as_str()),
+                ("source", "main_web"),
+                ("refresh_token", credentials.refresh_token.as_str()),
+            ]);
+        let (payload, set_cookies): (CookieRefreshPayload, _) = send(request).await?;
+        let mut refreshed = LoginCredentials {
+            cookies: credentials.cookies.clone(),
+            refresh_token: payload.refresh_token,
+            expires_at: credentials.expires_at,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
+        refreshed.merge_cookies(set_cookies.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+        let request = self
+            .with_cookies(
+                self.client
+                    .post(format!("{}{CONFIRM_REFRESH_PATH}", self.passport_base)),
+                &refreshed,
+            )
+            .form(&[
+                ("csrf", refreshed.csrf().unwrap_or_default()),
+                ("refresh_token", credentials.refresh_token.as_str()),
+            ]);
+        send_checked::<serde_json::Value>(request).await?;
+        Ok(refreshed)
+    }
+
+    async fn refresh_csrf(
+        &self,
+        credentials: &LoginCredentials,
+        timestamp_ms: i64,
+    ) -> Result<String, FavlistError> {
+        let path = correspond_path(timestamp_ms)?;
+        let response = self
+            .with_cookies(
+                self.client
+                    .get(format!("{}{CORRESPOND_PATH}/{path}", self.www_base)),
+                credentials,
+            )
+            .send()
+            .await
+            .map_err(FavlistError::Request)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FavlistError::Http {
+                status: status.as_u16(),
+            });
+        }
+        let html = response.text().await.map_err(FavlistError::Request)?;
+        extract_refresh_csrf(&html)
+            .ok_or_else(|| FavlistError::Login("correspond 页面缺少refresh_csrf".to_string()))
+    }
+
+    fn with_cookies(
+        &self,
+        request: RequestBuilder,
+        credentials: &LoginCredentials,
+    ) -> RequestBuilder {
+        request.header(COOKIE, credentials.cookie_header())
+    }
+}
+
+/// 以终端字符绘制二维码，两行像素合并为一行字符。
+pub fn render_qr(content: &str) -> Result<String, FavlistError> {
+    let code = QrCode::new(content.as_bytes())
+        .map_err(|err| FavlistError::Login(format!("二维码生成失败: {err}")))?;
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+/// 生成二维码并等待扫码确认，`on_ticket` 用于展示二维码。
+pub fn login_with_qr_blocking(
+    options: LoginOptions,
+    on_ticket: impl FnOnce(&QrTicket),
+    on_status: impl FnMut(&QrPollStatus),
+) -> Result<LoginCredentials, FavlistError> {
+    blocking_runtime()?.block_on(async {
+        let login = QrLogin::new(options)?;
+        let ticket = login.generate().await?;
+        on_ticket(&ticket);
+        login.wait_for_login(&ticket, on_status).await
+    })
+}
+
+/// 读取凭据文件并在需要时刷新写回，返回是否发生了刷新。
+pub fn refresh_credentials_file_blocking(
+    path: &Path,
+    options: LoginOptions,
+) -> Result<bool, FavlistError> {
+    let credentials = LoginCredentials::load(path)?;
+    let refreshed = blocking_runtime()?
+        .block_on(async { QrLogin::new(options)?.refresh_if_needed(&credentials).await })?;
+    match refreshed {
+        Some(refreshed) => {
+            refreshed.save(path)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+async fn send<T: DeserializeOwned>(
+    request: RequestBuilder,
+) -> Result<(T, Vec<(String, String)>), FavlistError> {
+    let (payload, set_cookies) = send_checked::<T>(request).await?;
+    let data = payload
+        .data
+        .ok_or_else(|| FavlistError::InvalidJson("响应缺少data字段".to_string()))?;
+    Ok((data, set_cookies))
+}
+
+/// 只检查返回码，不要求响应带 `data` 字段。
+async fn send_checked<T: DeserializeOwned>(
+    request: RequestBuilder,
+) -> Result<(ApiResponse<T>, Vec<(String, String)>), FavlistError> {
+    let response = request.send().await.map_err(FavlistError::Request)?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FavlistError::Http {
+            status: status.as_u16(),
+        });
+    }
+    let set_cookies = parse_set_cookies(response.headers());
+    let bytes = response.bytes().await.map_err(FavlistError::Request)?;
+    let payload: ApiResponse<T> =
+        serde_json::from_slice(&bytes).map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+    if payload.code != 0 {
+        return Err(FavlistError::Api {
+            code: payload.code,
+            message: payload.message.unwrap_or_else(|| "unknown".to_string()),
+        });
+    }
+    Ok((payload, set_cookies))
+}
+
+/// 解析 Set-Cookie 的 `name=value`，并以 `Expires` 记录其中的过期时间（Unix 秒）。
+fn parse_set_cookies(headers: &HeaderMap) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    for value in headers.get_all(SET_COOKIE) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        let mut parts = value.split(';');
+        let Some((name, cookie)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+            continue;
+        };
+        pairs.push((name.trim().to_string(), cookie.trim().to_string()));
+        if name.trim() != "SESSDATA" {
+            continue;
+        }
+        for attribute in parts {
+            if let Some((key, date)) = attribute.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("expires") {
+                    if let Ok(expires) =
+                        chrono::DateTime::parse_from_rfc2822(&date.trim().replace('-', " "))
+                    {
+                        pairs.push(("Expires".to_string(), expires.timestamp().to_string()));
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+fn correspond_path(timestamp_ms: i64) -> Result<String, FavlistError> {
+    let key = RsaPublicKey::from_public_key_pem(CORRESPOND_PUBLIC_KEY)
+        .map_err(|err| FavlistError::Login(format!("RSA公钥无效: {err}")))?;
+    let encrypted = key
+        .encrypt(
+            &mut rand::thread_rng(),
+            Oaep::new::<Sha256>(),
+            format!("refresh_{timestamp_ms}").as_bytes(),
+        )
+        .map_err(|err| FavlistError::Login(format!("correspondPath生成失败: {err}")))?;
+    Ok(hex::encode(encrypted))
+}
+
+fn extract_refresh_csrf(html: &str) -> Option<String> {
+    let start = html.find(r#"<div id="1-name">"#)? + r#"<div id="1-name">"#.len();
+    let end = html[start..].find("</div>")? + start;
+    let value = html[start..end].trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
@@ -1,5 +1,7 @@
 use chrono::{DateTime, FixedOffset};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::bvid::{av_to_bv, canonical_video_id};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FolderInfo {
@@ -10,13 +12,114 @@ pub struct FolderInfo {
     pub media_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct VideoEntry {
     pub bv_id: String,
     pub title: String,
     pub fav_title: String,
     pub timestamp: String,
     pub aid: Option<i64>,
+    #[serde(default)]
+    pub upper: Uploader,
+    /// 总时长（秒）
+    #[serde(default)]
+    pub duration: i64,
+    /// 分P数量
+    #[serde(default)]
+    pub page_count: i64,
+    #[serde(default)]
+    pub fav_time: i64,
+    #[serde(default)]
+    pub pubtime: i64,
+    #[serde(default)]
+    pub ctime: i64,
+    #[serde(default)]
+    pub cover: String,
+    #[serde(default)]
+    pub intro: String,
+    #[serde(default)]
+    pub attr: i32,
+    #[serde(default)]
+    pub stats: VideoStats,
+    #[serde(default)]
+    pub status: VideoStatus,
+}
+
+/// 收藏条目的可用状态，由收藏接口返回的 `attr` 与标题推断。
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoStatus {
+    #[default]
+    Available,
+    /// 稿件已被删除（`attr` 末位为 1，或标题为“已失效视频”）。
+    Deleted,
+    /// 其余非零 `attr`，通常为地区限制等原因导致无法访问。
+    RegionBlocked,
+}
+
+/// 对已失效条目（删除、地区限制）的处理方式。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidVideoPolicy {
+    /// 与正常条目一样写入并标注状态。
+    #[default]
+    Include,
+    /// 直接忽略。
+    Skip,
+    /// 不写入主结果，单独汇报。
+    Separate,
+}
+
+pub const INVALID_VIDEO_TITLE: &str = "已失效视频";
+
+impl VideoStatus {
+    pub fn classify(attr: i32, title: &str) -> Self {
+        if attr & 1 == 1 || title.trim() == INVALID_VIDEO_TITLE {
+            VideoStatus::Deleted
+        } else if attr != 0 {
+            VideoStatus::RegionBlocked
+        } else {
+            VideoStatus::Available
+        }
+    }
+
+    pub fn is_available(self) -> bool {
+        self == VideoStatus::Available
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VideoStatus::Available => "available",
+            VideoStatus::Deleted => "deleted",
+            VideoStatus::RegionBlocked => "region_blocked",
+        }
+    }
+
+    /// 解析 CSV 中记录的状态，空值与未知值按可用处理。
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "deleted" => VideoStatus::Deleted,
+            "region_blocked" => VideoStatus::RegionBlocked,
+            _ => VideoStatus::Available,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Uploader {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub mid: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VideoStats {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub play: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub danmaku: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub collect: i64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -36,6 +139,270 @@ pub struct FolderInfoPayload {
     pub media_count: i64,
 }
 
+/// `/x/v3/fav/folder/created/list-all` 的响应，用户没有收藏夹时 `list` 为 `null`。
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreatedFoldersPayload {
+    #[serde(default)]
+    pub count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub list: Vec<FolderInfoPayload>,
+}
+
+/// `/x/space/fav/season/list` 的响应：收藏页中收藏的合集（`ctype=21`）。
+#[derive(Debug, Clone, Deserialize)]
+pub struct CollectedSeasonPayload {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub info: CollectedSeasonInfo,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub medias: Vec<VideoItem>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CollectedSeasonInfo {
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub media_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub upper: Uploader,
+}
+
+/// 合集与系列接口共用的稿件条目。
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ArchiveItem {
+    #[serde(default)]
+    pub aid: Option<i64>,
+    #[serde(default)]
+    pub bvid: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub pic: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub duration: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub pubdate: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub ctime: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub stat: ArchiveStat,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ArchiveStat {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub view: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub danmaku: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub favorite: i64,
+}
+
+impl ArchiveItem {
+    /// 转换为收藏夹条目；合集/系列没有收藏时间，`fav_time` 以发布时间代替。
+    pub fn into_video_item(self, upper: &Uploader) -> VideoItem {
+        VideoItem {
+            bv_id: String::new(),
+            bvid: self.bvid,
+            id: self.aid,
+            title: self.title,
+            fav_time: self.pubdate,
+            upper: upper.clone(),
+            duration: self.duration,
+            page: 0,
+            pubtime: self.pubdate,
+            ctime: self.ctime,
+            cover: self.pic,
+            intro: String::new(),
+            attr: 0,
+            cnt_info: VideoStats {
+                play: self.stat.view,
+                danmaku: self.stat.danmaku,
+                collect: self.stat.favorite,
+            },
+        }
+    }
+}
+
+/// `/x/polymer/web-space/seasons_archives_list` 的响应。
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeasonArchivesPayload {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub archives: Vec<ArchiveItem>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub meta: SeasonMeta,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub page: SeasonPage,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SeasonMeta {
+    #[serde(default)]
+    pub season_id: i64,
+    #[serde(default)]
+    pub mid: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SeasonPage {
+    #[serde(default)]
+    pub page_num: i64,
+    #[serde(default)]
+    pub page_size: i64,
+    #[serde(default)]
+    pub total: i64,
+}
+
+/// `/x/series/archives` 的响应。
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeriesArchivesPayload {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub archives: Vec<ArchiveItem>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub page: SeriesPage,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SeriesPage {
+    #[serde(default)]
+    pub num: i64,
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub total: i64,
+}
+
+/// `/x/series/series` 的响应。
+#[derive(Debug, Clone, Deserialize)]
+pub struct SeriesInfoPayload {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub meta: SeriesMeta,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SeriesMeta {
+    #[serde(default)]
+    pub series_id: i64,
+    #[serde(default)]
+    pub mid: i64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub total: i64,
+}
+
+/// `/x/v2/history/toview` 的响应，一次返回全部稍后再看条目。
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchLaterPayload {
+    #[serde(default)]
+    pub count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub list: Vec<WatchLaterItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WatchLaterItem {
+    #[serde(flatten)]
+    pub archive: ArchiveItem,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub owner: Uploader,
+    /// 加入稍后再看的时间（Unix 秒）
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub add_at: i64,
+    /// 分P数量
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub videos: i64,
+}
+
+impl WatchLaterItem {
+    /// 以加入时间作为 `fav_time`。
+    pub fn into_video_item(self) -> VideoItem {
+        let mut item = self.archive.into_video_item(&self.owner);
+        item.fav_time = self.add_at;
+        item.page = self.videos;
+        item
+    }
+}
+
+/// `/x/web-interface/history/cursor` 的响应，按游标向前翻页。
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryPayload {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cursor: HistoryCursor,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub list: Vec<HistoryItem>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub struct HistoryCursor {
+    #[serde(default)]
+    pub max: i64,
+    #[serde(default)]
+    pub view_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryItem {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cover: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub history: HistoryRef,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub view_at: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub author_mid: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub author_name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub duration: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub videos: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HistoryRef {
+    /// 稿件 aid
+    #[serde(default)]
+    pub oid: i64,
+    #[serde(default)]
+    pub bvid: String,
+    #[serde(default)]
+    pub business: String,
+}
+
+impl HistoryItem {
+    /// 以观看时间作为 `fav_time`。
+    pub fn into_video_item(self) -> VideoItem {
+        VideoItem {
+            bv_id: String::new(),
+            bvid: self.history.bvid,
+            id: (self.history.oid > 0).then_some(self.history.oid),
+            title: self.title,
+            fav_time: self.view_at,
+            upper: Uploader {
+                mid: self.author_mid,
+                name: self.author_name,
+            },
+            duration: self.duration,
+            page: self.videos,
+            pubtime: 0,
+            ctime: 0,
+            cover: self.cover,
+            intro: String::new(),
+            attr: 0,
+            cnt_info: VideoStats::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ResourceListPayload {
     #[serde(default)]
@@ -54,6 +421,37 @@ pub struct VideoItem {
     pub id: Option<i64>,
     #[serde(default)]
     pub title: String,
+    /// 收藏时间（Unix 秒），收藏夹按此字段倒序排列。
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub fav_time: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub upper: Uploader,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub duration: i64,
+    /// 分P数量
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub page: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub pubtime: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub ctime: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cover: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub intro: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub attr: i32,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub cnt_info: VideoStats,
+}
+
+/// 接口偶尔以 `null` 代替缺省字段，统一按默认值处理。
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
 }
 
 impl FolderInfoPayload {
@@ -69,13 +467,21 @@ impl FolderInfoPayload {
 }
 
 impl VideoItem {
+    pub fn status(&self) -> VideoStatus {
+        VideoStatus::classify(self.attr, &self.title)
+    }
+
     pub fn resolve_bvid(&self) -> Option<String> {
         if !self.bv_id.trim().is_empty() {
-            Some(self.bv_id.trim().to_string())
+            Some(canonical_video_id(&self.bv_id))
         } else if !self.bvid.trim().is_empty() {
-            Some(self.bvid.trim().to_string())
+            Some(canonical_video_id(&self.bvid))
         } else {
-            self.id.map(|aid| format!("av{aid}"))
+            let aid = self.id?;
+            u64::try_from(aid)
+                .ok()
+                .and_then(|aid| av_to_bv(aid).ok())
+                .or_else(|| Some(format!("av{aid}")))
         }
     }
 }
@@ -91,3 +497,35 @@ pub struct InventorySnapshot {
     pub generated_at: DateTime<FixedOffset>,
     pub items: Vec<InventoryItem>,
 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QrGeneratePayload {
+    pub url: String,
+    pub qrcode_key: String,
+}
+
+/// 扫码轮询结果，`code` 为登录状态而非接口错误码。
+#[derive(Debug, Clone, Deserialize)]
+pub struct QrPollPayload {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub refresh_token: String,
+    #[serde(default)]
+    pub timestamp: i64,
+    pub code: i32,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CookieInfoPayload {
+    pub refresh: bool,
+    #[serde(default)]
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CookieRefreshPayload {
+    pub refresh_token: String,
+}
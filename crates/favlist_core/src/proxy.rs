@@ -0,0 +1,62 @@
+use reqwest::{NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::errors::FavlistError;
+
+const SUPPORTED_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+
+/// 请求代理。`url` 支持 `http://`、`https://`、`socks5://` 与 `socks5h://`（由代理解析域名），
+/// 认证信息既可写在 `url` 中，也可经 `username`/`password` 单独提供。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// 不经代理的主机，写法同 `NO_PROXY`（如 `localhost`、`127.0.0.1`、`.example.com`）。
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn with_no_proxy<S: Into<String>>(mut self, hosts: impl IntoIterator<Item = S>) -> Self {
+        self.no_proxy.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    /// 校验代理地址并转换为 `reqwest::Proxy`，同步与异步客户端均可使用。
+    pub fn to_proxy(&self) -> Result<Proxy, FavlistError> {
+        let url = Url::parse(self.url.trim())
+            .map_err(|err| FavlistError::InvalidUrl(format!("代理地址无效: {err}")))?;
+        if !SUPPORTED_SCHEMES.contains(&url.scheme()) {
+            return Err(FavlistError::InvalidUrl(format!(
+                "不支持的代理协议: {}（可用 http/https/socks5/socks5h）",
+                url.scheme()
+            )));
+        }
+        let mut proxy = Proxy::all(url.as_str())
+            .map_err(|err| FavlistError::InvalidUrl(format!("代理地址无效: {err}")))?;
+        if let Some(username) = self.username.as_deref() {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or_default());
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")));
+        }
+        Ok(proxy)
+    }
+}
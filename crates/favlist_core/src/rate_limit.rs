@@ -0,0 +1,50 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// 最小间隔限速器；克隆后共享同一时间槽，便于多个导出任务共用请求预算。
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            interval: min_interval,
+            next_slot: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 按每秒请求数构造，非正数或非有限值视为不限速。
+    pub fn per_second(requests_per_second: f64) -> Self {
+        let interval = if requests_per_second.is_finite() && requests_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+        Self::new(interval)
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 等待直到获得下一个请求时间槽。
+    pub async fn acquire(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let wait_until = {
+            let mut next_slot = self.next_slot.lock();
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(wait_until).await;
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::errors::FavlistError;
+
+/// B 站风控相关的业务错误码，出现时通常稍候即可恢复。
+pub const RISK_CONTROL_CODES: [i32; 4] = [-352, -412, -509, -799];
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// 含首次请求在内的最大尝试次数，1 表示不重试。
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// 计算第 `retry` 次重试（从 1 开始）前的等待时间。
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+        if !self.jitter || delay.is_zero() {
+            return delay;
+        }
+        let half = delay / 2;
+        let spread = fastrand::u64(0..=half.as_millis() as u64);
+        half + Duration::from_millis(spread)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryEvent {
+    /// 即将进行的尝试序号（从 2 开始）。
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub delay: Duration,
+    pub reason: String,
+}
+
+pub type RetryObserver = Arc<dyn Fn(&RetryEvent) + Send + Sync + 'static>;
+
+impl FavlistError {
+    /// 网络错误、5xx、412/429 与风控错误码可重试；`-404`、`-403` 等业务错误直接失败。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FavlistError::Request(err) => !err.is_builder() && !err.is_decode(),
+            FavlistError::Http { status } => *status >= 500 || *status == 412 || *status == 429,
+            FavlistError::Api { code, .. } => RISK_CONTROL_CODES.contains(code),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use rusqlite::{params, Connection};
+
+use crate::bvid::canonical_video_id;
+use crate::csv_utils::{append_entries, load_existing_bv_ids, migrate_csv, CsvMigration};
+use crate::errors::FavlistError;
+use crate::models::VideoEntry;
+
+/// 导出结果的落地目标。实现需以 `bv_id` 去重：`load_existing_ids` 返回的条目不会再次写入。
+pub trait ExportSink: Send {
+    fn path(&self) -> &Path;
+
+    /// 读取目标中已有的 BV 号；目标不存在时返回空集合。
+    fn load_existing_ids(&mut self) -> Result<HashSet<String>, FavlistError>;
+
+    /// 追加一批新条目，返回写入数量。
+    fn write(&mut self, entries: &[VideoEntry]) -> Result<usize, FavlistError>;
+
+    /// 导出结束时调用，确保即使没有新条目目标文件也存在。
+    fn finish(&mut self) -> Result<(), FavlistError>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    Jsonl,
+    Sqlite,
+}
+
+impl ExportFormat {
+    /// 按扩展名推断格式，无法识别时返回 `None`。
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "jsonl" | "ndjson" => Some(ExportFormat::Jsonl),
+            "db" | "sqlite" | "sqlite3" => Some(ExportFormat::Sqlite),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = FavlistError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "jsonl" | "ndjson" => Ok(ExportFormat::Jsonl),
+            "sqlite" | "db" => Ok(ExportFormat::Sqlite),
+            other => Err(FavlistError::Other(format!("不支持的导出格式: {other}"))),
+        }
+    }
+}
+
+pub fn create_sink(
+    format: ExportFormat,
+    path: &Path,
+    encoding: &str,
+) -> Result<Box<dyn ExportSink>, FavlistError> {
+    Ok(match format {
+        ExportFormat::Csv => Box::new(CsvSink::open(path, encoding)?),
+        ExportFormat::Json => Box::new(JsonSink::new(path)),
+        ExportFormat::Jsonl => Box::new(JsonlSink::new(path)),
+        ExportFormat::Sqlite => Box::new(SqliteSink::open(path)?),
+    })
+}
+
+fn ensure_parent(path: &Path) -> Result<(), FavlistError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// 打开时把旧版表头迁移到当前版本（只检查一次），写入时缓存表头列顺序。
+pub struct CsvSink {
+    path: PathBuf,
+    encoding: String,
+    columns: Option<Vec<String>>,
+    migration: Option<CsvMigration>,
+}
+
+impl CsvSink {
+    pub fn open(path: &Path, encoding: &str) -> Result<Self, FavlistError> {
+        let migration = migrate_csv(path, encoding)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            encoding: encoding.to_string(),
+            columns: None,
+            migration,
+        })
+    }
+
+    /// 打开时发生的表头迁移。
+    pub fn migration(&self) -> Option<&CsvMigration> {
+        self.migration.as_ref()
+    }
+}
+
+impl ExportSink for CsvSink {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load_existing_ids(&mut self) -> Result<HashSet<String>, FavlistError> {
+        load_existing_bv_ids(&self.path, &self.encoding)
+    }
+
+    fn write(&mut self, entries: &[VideoEntry]) -> Result<usize, FavlistError> {
+        append_entries(&self.path, &self.encoding, entries, &mut self.columns)
+    }
+
+    fn finish(&mut self) -> Result<(), FavlistError> {
+        self.write(&[]).map(|_| ())
+    }
+}
+
+/// 整个文件为一个 JSON 数组；写入时覆盖末尾的 `]` 追加新条目并重新闭合，
+/// 每页只写入新增部分，页与页之间文件始终是合法的 JSON。
+pub struct JsonSink {
+    path: PathBuf,
+    /// 末尾 `]` 的偏移与数组是否已有条目，首次读写时确定。
+    tail: Option<JsonTail>,
+}
+
+#[derive(Clone, Copy)]
+struct JsonTail {
+    closing: u64,
+    has_entries: bool,
+}
+
+impl JsonSink {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            tail: None,
+        }
+    }
+
+    /// 解析已有文件，返回其中的条目；文件不存在或为空时视为空数组。
+    fn read_existing(&mut self) -> Result<Vec<serde_json::Value>, FavlistError> {
+        if !self.path.exists() || fs::metadata(&self.path)?.len() == 0 {
+            self.tail = None;
+            return Ok(Vec::new());
+        }
+        let file = File::open(&self.path)?;
+        let values: Vec<serde_json::Value> = serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+        let closing = closing_bracket_offset(&self.path)?;
+        self.tail = Some(JsonTail {
+            closing,
+            has_entries: !values.is_empty(),
+        });
+        Ok(values)
+    }
+
+    fn append(&mut self, entries: &[VideoEntry]) -> Result<(), FavlistError> {
+        if self.tail.is_none() {
+            self.read_existing()?;
+        }
+        ensure_parent(&self.path)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        let mut has_entries = match self.tail {
+            Some(tail) => {
+                file.seek(SeekFrom::Start(tail.closing))?;
+                tail.has_entries
+            }
+            None => {
+                file.write_all(b"[")?;
+                false
+            }
+        };
+        let mut writer = BufWriter::new(&mut file);
+        for entry in entries {
+            writer.write_all(if has_entries { b",\n" } else { b"\n" })?;
+            let pretty = serde_json::to_string_pretty(entry)
+                .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+            for (index, line) in pretty.lines().enumerate() {
+                if index > 0 {
+                    writer.write_all(b"\n")?;
+                }
+                write!(writer, "  {line}")?;
+            }
+            has_entries = true;
+        }
+        if has_entries {
+            writer.write_all(b"\n")?;
+        }
+        let closing = writer.stream_position()?;
+        writer.write_all(b"]")?;
+        writer.flush()?;
+        drop(writer);
+        file.set_len(closing + 1)?;
+        self.tail = Some(JsonTail {
+            closing,
+            has_entries,
+        });
+        Ok(())
+    }
+}
+
+/// 从文件末尾向前查找闭合数组的 `]`。
+fn closing_bracket_offset(path: &Path) -> Result<u64, FavlistError> {
+    let mut file = File::open(path)?;
+    let mut end = file.metadata()?.len();
+    let mut buf = [0u8; 512];
+    while end > 0 {
+        let start = end.saturating_sub(buf.len() as u64);
+        let chunk = &mut buf[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|byte| !byte.is_ascii_whitespace()) {
+            if chunk[pos] == b']' {
+                return Ok(start + pos as u64);
+            }
+            break;
+        }
+        end = start;
+    }
+    Err(FavlistError::InvalidJson(format!(
+        "{} 不是 JSON 数组",
+        path.display()
+    )))
+}
+
+/// 读取条目中的 `bv_id`，av号统一换算为 BV号，与 CSV 的去重口径一致。
+fn value_video_id(value: &serde_json::Value) -> Option<String> {
+    let bvid = value.get("bv_id")?.as_str()?.trim();
+    (!bvid.is_empty()).then(|| canonical_video_id(bvid))
+}
+
+impl ExportSink for JsonSink {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load_existing_ids(&mut self) -> Result<HashSet<String>, FavlistError> {
+        Ok(self
+            .read_existing()?
+            .iter()
+            .filter_map(value_video_id)
+            .collect())
+    }
+
+    fn write(&mut self, entries: &[VideoEntry]) -> Result<usize, FavlistError> {
+        if !entries.is_empty() {
+            self.append(entries)?;
+        }
+        Ok(entries.len())
+    }
+
+    fn finish(&mut self) -> Result<(), FavlistError> {
+        if !self.path.exists() || fs::metadata(&self.path)?.len() == 0 {
+            self.tail = None;
+            self.append(&[])?;
+        }
+        Ok(())
+    }
+}
+
+/// 每行一个 JSON 对象，写入时直接追加。
+pub struct JsonlSink {
+    path: PathBuf,
+}
+
+impl JsonlSink {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+impl ExportSink for JsonlSink {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 末行不完整（写入中途崩溃）时截掉该行，其余行解析失败仍报错。
+    fn load_existing_ids(&mut self) -> Result<HashSet<String>, FavlistError> {
+        let mut existing = HashSet::new();
+        if !self.path.exists() {
+            return Ok(existing);
+        }
+        let content = fs::read_to_string(&self.path)?;
+        let complete_len = content.rfind('\n').map_or(0, |pos| pos + 1);
+        let (complete, partial) = content.split_at(complete_len);
+        for line in complete.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: serde_json::Value = serde_json::from_str(line)
+                .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+            existing.extend(value_video_id(&value));
+        }
+        if !partial.trim().is_empty() {
+            let file = OpenOptions::new().append(true).open(&self.path)?;
+            match serde_json::from_str::<serde_json::Value>(partial) {
+                // 只缺换行符的完整对象补上换行，后续追加不会与之连成一行。
+                Ok(value) => {
+                    existing.extend(value_video_id(&value));
+                    (&file).write_all(b"\n")?;
+                }
+                Err(_) => file.set_len(complete_len as u64)?,
+            }
+        }
+        Ok(existing)
+    }
+
+    fn write(&mut self, entries: &[VideoEntry]) -> Result<usize, FavlistError> {
+        ensure_parent(&self.path)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        for entry in entries {
+            serde_json::to_writer(&mut writer, entry)
+                .map_err(|err| FavlistError::InvalidJson(err.to_string()))?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+        Ok(entries.len())
+    }
+
+    fn finish(&mut self) -> Result<(), FavlistError> {
+        self.write(&[]).map(|_| ())
+    }
+}
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS videos (
+    bv_id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    fav_name TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    status TEXT NOT NULL,
+    aid INTEGER,
+    upper_mid INTEGER NOT NULL,
+    upper_name TEXT NOT NULL,
+    duration INTEGER NOT NULL,
+    page_count INTEGER NOT NULL,
+    fav_time INTEGER NOT NULL,
+    pubtime INTEGER NOT NULL,
+    ctime INTEGER NOT NULL,
+    cover TEXT NOT NULL,
+    intro TEXT NOT NULL,
+    attr INTEGER NOT NULL,
+    play INTEGER NOT NULL,
+    danmaku INTEGER NOT NULL,
+    collect INTEGER NOT NULL
+);
+";
+
+/// 写入 SQLite 的 `videos` 表，`bv_id` 为主键，重复条目被忽略。
+pub struct SqliteSink {
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl SqliteSink {
+    pub fn open(path: &Path) -> Result<Self, FavlistError> {
+        ensure_parent(path)?;
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SQLITE_SCHEMA)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            conn,
+        })
+    }
+}
+
+impl ExportSink for SqliteSink {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load_existing_ids(&mut self) -> Result<HashSet<String>, FavlistError> {
+        let mut stmt = self.conn.prepare("SELECT bv_id FROM videos")?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        Ok(ids)
+    }
+
+    fn write(&mut self, entries: &[VideoEntry]) -> Result<usize, FavlistError> {
+        let tx = self.conn.transaction()?;
+        let mut written = 0usize;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR IGNORE INTO videos (
+                    bv_id, title, fav_name, timestamp, status, aid, upper_mid, upper_name,
+                    duration, page_count, fav_time, pubtime, ctime, cover, intro, attr,
+                    play, danmaku, collect
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+            )?;
+            for entry in entries {
+                written += stmt.execute(params![
+                    entry.bv_id,
+                    entry.title,
+                    entry.fav_title,
+                    entry.timestamp,
+                    entry.status.as_str(),
+                    entry.aid,
+                    entry.upper.mid,
+                    entry.upper.name,
+                    entry.duration,
+                    entry.page_count,
+                    entry.fav_time,
+                    entry.pubtime,
+                    entry.ctime,
+                    entry.cover,
+                    entry.intro,
+                    entry.attr,
+                    entry.stats.play,
+                    entry.stats.danmaku,
+                    entry.stats.collect,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(written)
+    }
+
+    fn finish(&mut self) -> Result<(), FavlistError> {
+        Ok(())
+    }
+}
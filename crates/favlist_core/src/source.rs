@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use url::Url;
+
+use crate::errors::FavlistError;
+
+/// 链接指向的导出来源。收藏夹以外的列表同样导出为 `VideoEntry`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FavSource {
+    /// 单个收藏夹（链接带 `fid` 或 `media_id`），包括收藏的他人收藏夹。
+    Folder { media_id: i64 },
+    /// 用户创建的全部收藏夹（`space.bilibili.com/<mid>/favlist`，不带 `fid`）。
+    UserFolders { up_mid: i64 },
+    /// 收藏页中收藏的合集（`favlist?fid=<season_id>&ctype=21`）。
+    CollectedSeason { season_id: i64 },
+    /// UP 主空间中的合集（`channel/collectiondetail?sid=` 或 `lists/<id>?type=season`）。
+    Season { mid: i64, season_id: i64 },
+    /// UP 主空间中的系列（`channel/seriesdetail?sid=` 或 `lists/<id>?type=series`）。
+    Series { mid: i64, series_id: i64 },
+    /// 登录用户的稍后再看（`www.bilibili.com/watchlater`），需要 Cookie。
+    WatchLater,
+    /// 登录用户的观看历史（`www.bilibili.com/account/history`），需要 Cookie。
+    History,
+}
+
+/// 稍后再看没有列表 ID，本地视频库以此伪 ID 记录。
+pub const WATCH_LATER_LIST_ID: i64 = -1;
+/// 观看历史在本地视频库中的伪 ID。
+pub const HISTORY_LIST_ID: i64 = -2;
+
+impl FavSource {
+    /// 单个列表的 ID；`UserFolders` 不对应单个列表，返回 `None`。
+    pub fn list_id(self) -> Option<i64> {
+        match self {
+            FavSource::Folder { media_id } => Some(media_id),
+            FavSource::UserFolders { .. } => None,
+            FavSource::CollectedSeason { season_id } | FavSource::Season { season_id, .. } => {
+                Some(season_id)
+            }
+            FavSource::Series { series_id, .. } => Some(series_id),
+            FavSource::WatchLater => Some(WATCH_LATER_LIST_ID),
+            FavSource::History => Some(HISTORY_LIST_ID),
+        }
+    }
+
+    pub fn requires_cookie(self) -> bool {
+        matches!(self, FavSource::WatchLater | FavSource::History)
+    }
+
+    pub fn kind(self) -> &'static str {
+        match self {
+            FavSource::Folder { .. } => "收藏夹",
+            FavSource::UserFolders { .. } => "用户收藏夹",
+            FavSource::CollectedSeason { .. } | FavSource::Season { .. } => "合集",
+            FavSource::Series { .. } => "系列",
+            FavSource::WatchLater => "稍后再看",
+            FavSource::History => "历史记录",
+        }
+    }
+
+    /// 规范化的网页链接，`parse_fav_source` 解析后得到同一来源。
+    pub fn canonical_url(self) -> String {
+        match self {
+            FavSource::Folder { media_id } => {
+                format!("https://www.bilibili.com/medialist/detail/ml{media_id}")
+            }
+            FavSource::UserFolders { up_mid } => {
+                format!("https://space.bilibili.com/{up_mid}/favlist")
+            }
+            FavSource::CollectedSeason { season_id } => {
+                format!("https://space.bilibili.com/favlist?fid={season_id}&ctype=21")
+            }
+            FavSource::Season { mid, season_id } => {
+                format!("https://space.bilibili.com/{mid}/lists/{season_id}?type=season")
+            }
+            FavSource::Series { mid, series_id } => {
+                format!("https://space.bilibili.com/{mid}/lists/{series_id}?type=series")
+            }
+            FavSource::WatchLater => "https://www.bilibili.com/watchlater/#/list".to_string(),
+            FavSource::History => "https://www.bilibili.com/account/history".to_string(),
+        }
+    }
+}
+
+/// 用户输入的来源引用。短链接需要经 [`BiliFavClient::resolve_source`] 跟随跳转后才能确定来源。
+///
+/// [`BiliFavClient::resolve_source`]: crate::client::BiliFavClient::resolve_source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceRef {
+    /// 纯数字 ID 或 `ml<id>`，均视为收藏夹 ID。
+    MediaId(i64),
+    /// 主站或移动端链接。
+    Url(FavSource),
+    /// `b23.tv` 等短链接。
+    ShortLink(Url),
+}
+
+impl SourceRef {
+    /// 已能确定的来源；短链接返回 `None`。
+    pub fn source(&self) -> Option<FavSource> {
+        match self {
+            SourceRef::MediaId(media_id) => Some(FavSource::Folder {
+                media_id: *media_id,
+            }),
+            SourceRef::Url(source) => Some(*source),
+            SourceRef::ShortLink(_) => None,
+        }
+    }
+}
+
+/// 识别来源引用的形式：纯数字 ID、`ml<id>`、网页链接（可省略协议）、移动端链接与短链接。
+pub fn parse_source_ref(input: &str) -> Result<SourceRef, FavlistError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(invalid_source(FORM_UNKNOWN, "输入为空"));
+    }
+    if trimmed.chars().all(|ch| ch.is_ascii_digit()) {
+        return parse_id("ID", trimmed)
+            .map(SourceRef::MediaId)
+            .map_err(|reason| invalid_source(FORM_NUMERIC, reason));
+    }
+    if let Some(id) = strip_ml_prefix(trimmed) {
+        return parse_id("ml号", id)
+            .map(SourceRef::MediaId)
+            .map_err(|reason| invalid_source(FORM_ML, reason));
+    }
+
+    let with_scheme = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("https://{trimmed}")
+    };
+    let url = Url::parse(&with_scheme)
+        .map_err(|err| invalid_source(FORM_UNKNOWN, format!("URL解析失败: {err}")))?;
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+    if SHORT_LINK_HOSTS.contains(&host.as_str()) {
+        return Ok(SourceRef::ShortLink(url));
+    }
+    let form = if host == "m.bilibili.com" {
+        FORM_MOBILE
+    } else {
+        FORM_WEB
+    };
+    url_source(&url)
+        .map(SourceRef::Url)
+        .map_err(|reason| invalid_source(form, reason))
+}
+
+/// 解析不需要联网即可确定的来源；短链接请改用 [`BiliFavClient::resolve_source`]。
+///
+/// [`BiliFavClient::resolve_source`]: crate::client::BiliFavClient::resolve_source
+pub fn parse_fav_source(fav_url: &str) -> Result<FavSource, FavlistError> {
+    match parse_source_ref(fav_url)? {
+        SourceRef::ShortLink(_) => Err(invalid_source(
+            FORM_SHORT_LINK,
+            "短链接需要联网解析跳转目标",
+        )),
+        other => Ok(other.source().expect("非短链接总能确定来源")),
+    }
+}
+
+pub(crate) const FORM_SHORT_LINK: &str = "短链接";
+const FORM_NUMERIC: &str = "纯数字ID";
+const FORM_ML: &str = "ml号";
+const FORM_WEB: &str = "网页链接";
+const FORM_MOBILE: &str = "移动端链接";
+const FORM_UNKNOWN: &str = "来源";
+
+const SHORT_LINK_HOSTS: [&str; 4] = ["b23.tv", "www.b23.tv", "bili2233.cn", "www.bili2233.cn"];
+
+pub(crate) fn invalid_source(form: &str, reason: impl Into<String>) -> FavlistError {
+    FavlistError::InvalidSource {
+        form: form.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn strip_ml_prefix(value: &str) -> Option<&str> {
+    let prefix = value.get(..2)?;
+    prefix.eq_ignore_ascii_case("ml").then(|| &value[2..])
+}
+
+fn url_source(url: &Url) -> Result<FavSource, String> {
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let has_folder_param = query.contains_key("fid") || query.contains_key("media_id");
+
+    if let Some(source) = account_list_source(url) {
+        return Ok(source);
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+
+    if matches!(host, "www.bilibili.com" | "bilibili.com" | "m.bilibili.com") {
+        if let ["medialist", "detail" | "play", id] | ["list", id] = segments.as_slice() {
+            if let Some(id) = strip_ml_prefix(id) {
+                return parse_id("ml号", id).map(|media_id| FavSource::Folder { media_id });
+            }
+        }
+    }
+
+    // 移动端空间页为 `m.bilibili.com/space/<mid>/...`，去掉前缀后与桌面端一致。
+    let space_segments = match (host, segments.as_slice()) {
+        ("space.bilibili.com", rest) => Some(rest),
+        ("m.bilibili.com", ["space", rest @ ..]) => Some(rest),
+        _ => None,
+    };
+    if let Some(segments) = space_segments {
+        if let Some(mid) = segments.first().and_then(|mid| mid.parse::<i64>().ok()) {
+            match &segments[1..] {
+                ["channel", "collectiondetail"] => {
+                    let season_id = id_param(&query, "sid")?;
+                    return Ok(FavSource::Season { mid, season_id });
+                }
+                ["channel", "seriesdetail"] => {
+                    let series_id = id_param(&query, "sid")?;
+                    return Ok(FavSource::Series { mid, series_id });
+                }
+                ["lists", id] => {
+                    let id = parse_id("lists", id)?;
+                    return Ok(match query.get("type").map(String::as_str) {
+                        Some("series") => FavSource::Series { mid, series_id: id },
+                        _ => FavSource::Season { mid, season_id: id },
+                    });
+                }
+                ["favlist"] if !has_folder_param => {
+                    return Ok(FavSource::UserFolders { up_mid: mid });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for key in ["media_id", "fid"] {
+        if query.contains_key(key) {
+            let id = id_param(&query, key)?;
+            return Ok(if query.get("ctype").map(String::as_str) == Some("21") {
+                FavSource::CollectedSeason { season_id: id }
+            } else {
+                FavSource::Folder { media_id: id }
+            });
+        }
+    }
+    Err("未在链接查询参数中找到fid或media_id，路径也不是ml号".to_string())
+}
+
+/// 单个收藏夹的网页链接，可直接作为 `ExportOptions::fav_url`。
+pub fn folder_url(up_mid: i64, media_id: i64) -> String {
+    format!("https://space.bilibili.com/{up_mid}/favlist?fid={media_id}")
+}
+
+/// 识别主站的稍后再看与历史记录页面，包括旧版 `#/list` 形式的链接。
+fn account_list_source(url: &Url) -> Option<FavSource> {
+    let host = url.host_str()?;
+    if !matches!(host, "www.bilibili.com" | "bilibili.com" | "m.bilibili.com") {
+        return None;
+    }
+    let segments: Vec<&str> = url
+        .path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    match segments.as_slice() {
+        ["watchlater", ..] | ["list", "watchlater", ..] => Some(FavSource::WatchLater),
+        ["account", "history", ..] | ["history", ..] => Some(FavSource::History),
+        _ => None,
+    }
+}
+
+fn id_param(query: &HashMap<String, String>, key: &str) -> Result<i64, String> {
+    let value = query.get(key).ok_or_else(|| format!("链接缺少{key}参数"))?;
+    parse_id(key, value)
+}
+
+fn parse_id(key: &str, value: &str) -> Result<i64, String> {
+    let cleaned = value.trim();
+    if cleaned.is_empty() || !cleaned.chars().all(|ch| ch.is_ascii_digit()) {
+        return Err(format!("{key}包含非法字符: {cleaned}"));
+    }
+    cleaned
+        .parse::<i64>()
+        .map_err(|_| format!("{key}数值无效: {cleaned}"))
+}
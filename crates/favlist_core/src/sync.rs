@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use futures::stream::{self, StreamExt};
+
+use crate::client::BiliFavClient;
+use crate::errors::ExportError;
+use crate::export::{
+    blocking_runtime, export_favlist_with_client, ExportEvent, ExportOptions, ExportResult,
+};
+
+pub const DEFAULT_SYNC_CONCURRENCY: usize = 4;
+
+/// 一个待同步的收藏夹；`name` 仅用于进度与结果展示。
+#[derive(Clone)]
+pub struct SyncJob {
+    pub name: String,
+    pub options: ExportOptions,
+}
+
+impl SyncJob {
+    pub fn new(name: impl Into<String>, options: ExportOptions) -> Self {
+        Self {
+            name: name.into(),
+            options,
+        }
+    }
+}
+
+/// 同步过程中的事件，`index` 为任务在输入列表中的序号。
+#[derive(Debug, Clone)]
+pub enum SyncEvent {
+    Started {
+        index: usize,
+        name: String,
+    },
+    Progress {
+        index: usize,
+        event: ExportEvent,
+    },
+    /// `error` 为空表示该任务成功。
+    Finished {
+        index: usize,
+        name: String,
+        error: Option<String>,
+    },
+}
+
+pub type SyncCallback = Arc<dyn Fn(SyncEvent) + Send + Sync + 'static>;
+
+#[derive(Clone)]
+pub struct SyncOptions {
+    /// 同时进行的导出任务上限，至少为 1。
+    pub max_concurrency: usize,
+    pub callback: Option<SyncCallback>,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_SYNC_CONCURRENCY,
+            callback: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SyncOutcome {
+    pub name: String,
+    pub result: Result<ExportResult, ExportError>,
+}
+
+/// 汇总结果，`outcomes` 与输入任务一一对应且顺序相同。
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub outcomes: Vec<SyncOutcome>,
+}
+
+impl SyncReport {
+    pub fn succeeded(&self) -> impl Iterator<Item = (&str, &ExportResult)> {
+        self.outcomes.iter().filter_map(|outcome| {
+            outcome
+                .result
+                .as_ref()
+                .ok()
+                .map(|result| (outcome.name.as_str(), result))
+        })
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = (&str, &ExportError)> {
+        self.outcomes.iter().filter_map(|outcome| {
+            outcome
+                .result
+                .as_ref()
+                .err()
+                .map(|err| (outcome.name.as_str(), err))
+        })
+    }
+
+    pub fn success_count(&self) -> usize {
+        self.succeeded().count()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.failed().count()
+    }
+
+    /// 全部任务新增条目数之和。
+    pub fn new_entry_count(&self) -> usize {
+        self.succeeded()
+            .map(|(_, result)| result.new_entries.len())
+            .sum()
+    }
+}
+
+/// 在同一个客户端上以有限并发同步多个收藏夹；单个任务失败不影响其余任务。
+///
+/// 各任务共享 `client` 的连接池、Cookie 与限速器，`options` 中的客户端设置被忽略，
+/// 见 `export_favlist_with_client`。
+pub async fn sync_folders(
+    client: &BiliFavClient,
+    jobs: Vec<SyncJob>,
+    options: SyncOptions,
+) -> SyncReport {
+    let callback = options.callback;
+    let mut outcomes: Vec<(usize, SyncOutcome)> = stream::iter(jobs.into_iter().enumerate())
+        .map(|(index, job)| {
+            let callback = callback.clone();
+            async move {
+                let outcome = run_job(client, index, job, callback).await;
+                (index, outcome)
+            }
+        })
+        .buffer_unordered(options.max_concurrency.max(1))
+        .collect()
+        .await;
+    outcomes.sort_by_key(|(index, _)| *index);
+    SyncReport {
+        outcomes: outcomes.into_iter().map(|(_, outcome)| outcome).collect(),
+    }
+}
+
+pub fn sync_folders_blocking(
+    client: &BiliFavClient,
+    jobs: Vec<SyncJob>,
+    options: SyncOptions,
+) -> Result<SyncReport, ExportError> {
+    Ok(blocking_runtime()?.block_on(sync_folders(client, jobs, options)))
+}
+
+async fn run_job(
+    client: &BiliFavClient,
+    index: usize,
+    job: SyncJob,
+    callback: Option<SyncCallback>,
+) -> SyncOutcome {
+    let SyncJob { name, mut options } = job;
+    if let Some(callback) = callback.as_ref() {
+        callback(SyncEvent::Started {
+            index,
+            name: name.clone(),
+        });
+        let job_callback = options.event_callback.take();
+        let sync_callback = Arc::clone(callback);
+        options.event_callback = Some(Arc::new(move |event: ExportEvent| {
+            if let Some(job_callback) = job_callback.as_ref() {
+                job_callback(event.clone());
+            }
+            sync_callback(SyncEvent::Progress { index, event });
+        }));
+    }
+    let result = export_favlist_with_client(client, options)
+        .await
+        .map_err(|err| err.context(format!("收藏夹「{name}」同步失败")));
+    if let Some(callback) = callback.as_ref() {
+        callback(SyncEvent::Finished {
+            index,
+            name: name.clone(),
+            error: result.as_ref().err().map(ToString::to_string),
+        });
+    }
+    SyncOutcome { name, result }
+}
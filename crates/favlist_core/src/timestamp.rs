@@ -1,7 +1,7 @@
 use chrono::Local;
-use url::Url;
 
 use crate::errors::FavlistError;
+use crate::source::parse_fav_source;
 
 const TS_FORMAT: &str = "%Y-%m-%dT%H-%M-%S";
 
@@ -9,26 +9,13 @@ pub fn current_timestamp() -> String {
     Local::now().format(TS_FORMAT).to_string()
 }
 
+/// 解析链接指向的单个列表 ID（收藏夹 `fid`/`media_id`、合集或系列 ID）。
 pub fn parse_media_id(fav_url: &str) -> Result<i64, FavlistError> {
-    let url = Url::parse(fav_url)
-        .map_err(|err| FavlistError::InvalidUrl(format!("URL解析失败: {err}")))?;
-    if let Some(query) = url.query() {
-        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
-            if key == "media_id" || key == "fid" {
-                let cleaned = value.trim();
-                if cleaned.chars().all(|ch| ch.is_ascii_digit()) {
-                    return cleaned.parse::<i64>().map_err(|_| {
-                        FavlistError::InvalidUrl(format!("{key}数值无效: {cleaned}"))
-                    });
-                } else {
-                    return Err(FavlistError::InvalidUrl(format!(
-                        "{key}包含非法字符: {cleaned}"
-                    )));
-                }
-            }
-        }
-    }
-    Err(FavlistError::InvalidUrl(
-        "未在链接查询参数中找到fid或media_id".to_string(),
-    ))
+    let source = parse_fav_source(fav_url)?;
+    source.list_id().ok_or_else(|| {
+        FavlistError::InvalidUrl(format!(
+            "{}链接未指定fid，请改用 export_all_folders 导出全部收藏夹",
+            source.kind()
+        ))
+    })
 }
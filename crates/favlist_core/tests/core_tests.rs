@@ -1,11 +1,26 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use fake_bilibili_api::{FakeServer, Scenario};
+use favlist_core::cookies::{parse_bbdown_data, parse_netscape_cookies};
 use favlist_core::{
-    export_favlist, load_existing_bv_ids, parse_media_id, read_csv_rows, write_entries,
-    ExportOptions, ExportProgress, ProgressCallback, VideoEntry,
+    av_to_bv, bv_to_av, classify_missing_videos, detect_schema, diff_changes, export_all_folders,
+    export_favlist, extract_bvids, folder_output_path, load_cookie_file, load_existing_bv_ids,
+    migrate_csv, normalize_video_id, parse_fav_source, parse_media_id, parse_source_ref,
+    read_csv_rows, refresh_credentials_file_blocking, render_qr, row_bvid, scan_directory_bvids,
+    sync_folders, write_entries, BiliFavClient, CancellationToken, ClientOptions, CookieFileFormat,
+    CsvSchema, CsvSink, DownloadState, ExportCallback, ExportError, ExportEvent, ExportFormat,
+    ExportOptions, ExportSink, FavSource, FavlistError, FixtureMode, IncrementalOptions,
+    InvalidVideoPolicy, JsonSink, JsonlSink, Library, LoginCredentials, LoginOptions, ProxyConfig,
+    QrLogin, QrPollStatus, RateLimiter, RetryPolicy, SourceRef, SqliteSink, SyncEvent, SyncJob,
+    SyncOptions, VideoEntry, VideoItem, VideoStatus, CSV_SCHEMA_VERSION, FIELDNAMES,
+    REMOVED_ARCHIVE_DIR, WATCH_LATER_LIST_ID,
 };
+use futures::{StreamExt, TryStreamExt};
 use httpmock::prelude::*;
+use httpmock::Regex;
 use serde_json::json;
 use tempfile::tempdir;
 
@@ -35,6 +50,7 @@ fn write_and_read_csv_with_gbk_encoding() -> TestResult<()> {
         fav_title: "收藏夹".to_string(),
         timestamp: "2025-11-02T12-00-00".to_string(),
         aid: Some(12345),
+        ..Default::default()
     }];
 
     write_entries(&csv_path, "gbk", &entries)?;
@@ -101,7 +117,8 @@ async fn export_favlist_writes_new_entries() -> TestResult<()> {
         timestamp: Some("2025-11-02T12-00-00".to_string()),
         extra_headers: Default::default(),
         base_url: Some(server.base_url()),
-        progress_callback: None,
+        event_callback: None,
+        ..Default::default()
     };
 
     let result = export_favlist(options.clone()).await?;
@@ -167,10 +184,10 @@ async fn export_progress_reports_updates() -> TestResult<()> {
 
     let dir = tempdir()?;
     let csv_path = dir.path().join("fav.csv");
-    let progress_events: Arc<Mutex<Vec<ExportProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let progress_events: Arc<Mutex<Vec<ExportEvent>>> = Arc::new(Mutex::new(Vec::new()));
     let capture = progress_events.clone();
-    let progress_handler: ProgressCallback = Arc::new(move |progress| {
-        capture.lock().unwrap().push(progress);
+    let progress_handler: ExportCallback = Arc::new(move |event| {
+        capture.lock().unwrap().push(event);
     });
 
     let options = ExportOptions {
@@ -183,19 +200,2207 @@ async fn export_progress_reports_updates() -> TestResult<()> {
         timestamp: Some("2025-11-03T00-00-00".to_string()),
         extra_headers: Default::default(),
         base_url: Some(server.base_url()),
-        progress_callback: Some(progress_handler),
+        event_callback: Some(progress_handler),
+        ..Default::default()
     };
 
-    export_favlist(options).await?;
+    export_favlist(options.clone()).await?;
 
     info_mock.assert();
     list_mock.assert();
 
+    {
+        let events = progress_events.lock().unwrap();
+        assert_eq!(events.len(), 4, "{events:?}");
+        assert!(matches!(
+            &events[0],
+            ExportEvent::FolderInfo { folder, total: Some(2) } if folder.title == "测试收藏夹"
+        ));
+        assert!(matches!(
+            events[1],
+            ExportEvent::PageFetched {
+                page: 1,
+                items: 2,
+                processed: 2,
+                total: Some(2)
+            }
+        ));
+        assert!(
+            matches!(&events[2], ExportEvent::WriteStarted { page: 1, count: 2, path } if *path == csv_path)
+        );
+        assert!(matches!(
+            events[3],
+            ExportEvent::Finished {
+                processed: 2,
+                new_entries: 2,
+                retries: 0,
+                stopped_early: false
+            }
+        ));
+        assert_eq!(events[1].to_string(), "已抓取第 1 页（2 条），累计 2/2 条");
+    }
+
+    // 再次导出时两个条目都已存在，只报告跳过而不写入。
+    progress_events.lock().unwrap().clear();
+    export_favlist(options).await?;
+    let events = progress_events.lock().unwrap();
+    let skipped: Vec<&str> = events
+        .iter()
+        .filter_map(|event| match event {
+            ExportEvent::DuplicateSkipped { bvid } => Some(bvid.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(skipped, ["BV1aa41117aa", "BV1bb41117bb"]);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, ExportEvent::WriteStarted { .. })));
+
+    Ok(())
+}
+
+fn fast_retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+        jitter: true,
+    }
+}
+
+#[tokio::test]
+async fn export_retries_risk_control_failures() -> TestResult<()> {
+    let server = MockServer::start();
+
+    let info_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/folder/info")
+            .query_param("media_id", "42");
+        then.status(412).body("Precondition Failed");
+    });
+
+    let dir = tempdir()?;
+    let progress_events: Arc<Mutex<Vec<ExportEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let capture = progress_events.clone();
+    let progress_handler: ExportCallback = Arc::new(move |event| {
+        capture.lock().unwrap().push(event);
+    });
+
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=42".to_string(),
+        csv_path: dir.path().join("fav.csv"),
+        base_url: Some(server.base_url()),
+        retry: fast_retry(3),
+        event_callback: Some(progress_handler),
+        ..Default::default()
+    };
+
+    let err = export_favlist(options).await.unwrap_err();
+    assert!(err.to_string().contains("412"));
+    info_mock.assert_hits(3);
+
     let events = progress_events.lock().unwrap();
-    assert_eq!(events.len(), 3);
-    let currents: Vec<u64> = events.iter().map(|event| event.current).collect();
-    assert_eq!(currents, vec![0, 1, 2]);
-    assert!(events.iter().all(|event| event.total == Some(2)));
+    let retries: Vec<(u64, u32)> = events
+        .iter()
+        .filter_map(|event| match event {
+            ExportEvent::RetryScheduled { retry, retries } => Some((*retries, retry.attempt)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(retries, vec![(1, 2), (2, 3)]);
+    assert_eq!(events.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn fatal_api_codes_are_not_retried() -> TestResult<()> {
+    let server = MockServer::start();
+
+    let info_mock = server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200)
+            .json_body(json!({"code": -404, "message": "啥都木有"}));
+    });
+
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        retry: fast_retry(5),
+        ..Default::default()
+    })?;
+
+    let err = client.get_folder_info(7).await.unwrap_err();
+    assert!(matches!(err, FavlistError::Api { code: -404, .. }));
+    assert!(!err.is_retryable());
+    info_mock.assert_hits(1);
+
+    let risk = FavlistError::Api {
+        code: -352,
+        message: "风控校验失败".to_string(),
+    };
+    assert!(risk.is_retryable());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limiter_is_shared_between_clients() -> TestResult<()> {
+    let server = MockServer::start();
+
+    let info_mock = server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 1, "title": "限速", "media_count": 0}
+        }));
+    });
+
+    let limiter = RateLimiter::new(Duration::from_millis(80));
+    let first = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        rate_limiter: Some(limiter.clone()),
+        ..Default::default()
+    })?;
+    let second = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        rate_limiter: Some(limiter),
+        ..Default::default()
+    })?;
+    let cloned = first.clone();
+
+    let started = Instant::now();
+    let (a, b, c) = tokio::join!(
+        first.get_folder_info(1),
+        second.get_folder_info(1),
+        cloned.get_folder_info(1)
+    );
+    a?;
+    b?;
+    c?;
+
+    assert!(started.elapsed() >= Duration::from_millis(160));
+    info_mock.assert_hits(3);
+
+    Ok(())
+}
+
+fn mock_page<'a>(
+    server: &'a MockServer,
+    media_id: &str,
+    page: &str,
+    body: serde_json::Value,
+) -> httpmock::Mock<'a> {
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/resource/list")
+            .query_param("media_id", media_id)
+            .query_param("pn", page);
+        then.status(200).json_body(body);
+    })
+}
+
+#[tokio::test]
+async fn export_keeps_pages_fetched_before_failure() -> TestResult<()> {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 77, "title": "分页收藏夹", "media_count": 3}
+        }));
+    });
+    mock_page(
+        &server,
+        "77",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [
+                    {"bvid": "BV1aa41117aa", "title": "第一页甲"},
+                    {"bvid": "BV1bb41117bb", "title": "第一页乙"}
+                ],
+                "has_more": true
+            }
+        }),
+    );
+    mock_page(
+        &server,
+        "77",
+        "2",
+        json!({"code": -404, "message": "啥都木有"}),
+    );
+
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("partial.csv");
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=77".to_string(),
+        csv_path: csv_path.clone(),
+        base_url: Some(server.base_url()),
+        retry: RetryPolicy::disabled(),
+        ..Default::default()
+    };
+
+    let err = export_favlist(options).await.unwrap_err();
+    assert!(err.to_string().contains("第 2 页"));
+
+    let existing = load_existing_bv_ids(&csv_path, "utf-8")?;
+    assert_eq!(existing.len(), 2);
+    assert!(existing.contains("BV1aa41117aa"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn video_item_stream_stops_when_dropped() -> TestResult<()> {
+    let server = MockServer::start();
+
+    let first_page = mock_page(
+        &server,
+        "88",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [
+                    {"bvid": "BV1cc41117cc", "title": "甲"},
+                    {"bvid": "BV1dd41117dd", "title": "乙"}
+                ],
+                "has_more": true
+            }
+        }),
+    );
+    let second_page = mock_page(
+        &server,
+        "88",
+        "2",
+        json!({"code": 0, "data": {"medias": [], "has_more": false}}),
+    );
+
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })?;
+
+    let items: Vec<_> = client.video_items(88, 2).take(2).try_collect().await?;
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].bvid, "BV1dd41117dd");
+    first_page.assert_hits(1);
+    second_page.assert_hits(0);
+
+    let pages = client.list_videos(88, 2).await?;
+    assert_eq!(pages.len(), 2);
+
+    Ok(())
+}
+
+fn mock_incremental_folder(server: &MockServer) -> (httpmock::Mock<'_>, httpmock::Mock<'_>) {
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 99, "title": "增量收藏夹", "media_count": 6}
+        }));
+    });
+    let first = mock_page(
+        server,
+        "99",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [
+                    {"bvid": "BV1ne41117ne", "title": "新视频", "fav_time": 1700000300},
+                    {"bvid": "BV1od41117od", "title": "旧视频一", "fav_time": 1700000200},
+                    {"bvid": "BV1ol41117ol", "title": "旧视频二", "fav_time": 1700000100}
+                ],
+                "has_more": true
+            }
+        }),
+    );
+    let second = mock_page(
+        server,
+        "99",
+        "2",
+        json!({"code": 0, "data": {"medias": [], "has_more": false}}),
+    );
+    (first, second)
+}
+
+#[tokio::test]
+async fn incremental_export_stops_after_known_entries() -> TestResult<()> {
+    let server = MockServer::start();
+    let (first, second) = mock_incremental_folder(&server);
+
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("inc.csv");
+    let known: Vec<VideoEntry> = ["BV1od41117od", "BV1ol41117ol"]
+        .iter()
+        .map(|bvid| VideoEntry {
+            bv_id: bvid.to_string(),
+            title: "旧视频".to_string(),
+            fav_title: "增量收藏夹".to_string(),
+            timestamp: "2025-11-01T00-00-00".to_string(),
+            aid: None,
+            ..Default::default()
+        })
+        .collect();
+    write_entries(&csv_path, "utf-8", &known)?;
+
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=99".to_string(),
+        csv_path: csv_path.clone(),
+        page_size: 3,
+        base_url: Some(server.base_url()),
+        incremental: Some(IncrementalOptions {
+            stop_after_known: 2,
+            since_fav_time: None,
+        }),
+        ..Default::default()
+    };
+
+    let result = export_favlist(options).await?;
+    assert!(result.stopped_early);
+    assert_eq!(result.new_entries.len(), 1);
+    assert_eq!(result.new_entries[0].bv_id, "BV1ne41117ne");
+    // 已导出过的条目同样计入本次抓取结果，供媒体库刷新 last_seen。
+    let seen: Vec<_> = result
+        .seen_entries
+        .iter()
+        .map(|e| e.bv_id.as_str())
+        .collect();
+    assert_eq!(seen, ["BV1ne41117ne", "BV1od41117od", "BV1ol41117ol"]);
+    assert_eq!(result.latest_fav_time, Some(1700000300));
+    first.assert_hits(1);
+    second.assert_hits(0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn incremental_export_stops_at_last_fav_time() -> TestResult<()> {
+    let server = MockServer::start();
+    let (first, second) = mock_incremental_folder(&server);
+
+    let dir = tempdir()?;
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=99".to_string(),
+        csv_path: dir.path().join("since.csv"),
+        page_size: 3,
+        base_url: Some(server.base_url()),
+        incremental: Some(IncrementalOptions {
+            stop_after_known: 0,
+            since_fav_time: Some(1700000200),
+        }),
+        ..Default::default()
+    };
+
+    let result = export_favlist(options).await?;
+    assert!(result.stopped_early);
+    first.assert_hits(1);
+    second.assert_hits(0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_captures_video_metadata() -> TestResult<()> {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 5, "title": "元数据", "media_count": 1}
+        }));
+    });
+    mock_page(
+        &server,
+        "5",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [{
+                    "id": 170001_i64,
+                    "bvid": "BV17x411w7KC",
+                    "title": " 带元数据的视频 ",
+                    "cover": "http://i0.hdslb.com/bfs/archive/cover.jpg",
+                    "intro": "简介",
+                    "page": 3,
+                    "duration": 754,
+                    "attr": 0,
+                    "upper": {"mid": 2, "name": "碧诗", "face": "http://face"},
+                    "cnt_info": {"collect": 12, "play": 3456, "danmaku": 78},
+                    "ctime": 1262275200_i64,
+                    "pubtime": 1262275300_i64,
+                    "fav_time": 1700000000_i64
+                }],
+                "has_more": false
+            }
+        }),
+    );
+
+    let dir = tempdir()?;
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=5".to_string(),
+        csv_path: dir.path().join("meta.csv"),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    };
+
+    let result = export_favlist(options).await?;
+    let entry = &result.new_entries[0];
+    assert_eq!(entry.title, "带元数据的视频");
+    assert_eq!(entry.aid, Some(170001));
+    assert_eq!(entry.upper.mid, 2);
+    assert_eq!(entry.upper.name, "碧诗");
+    assert_eq!(entry.duration, 754);
+    assert_eq!(entry.page_count, 3);
+    assert_eq!(entry.fav_time, 1700000000);
+    assert_eq!(entry.pubtime, 1262275300);
+    assert_eq!(entry.ctime, 1262275200);
+    assert_eq!(entry.stats.play, 3456);
+    assert_eq!(entry.stats.danmaku, 78);
+    assert_eq!(entry.stats.collect, 12);
+    assert_eq!(entry.intro, "简介");
+
+    Ok(())
+}
+
+fn mock_folder_with_invalid_items(server: &MockServer) {
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 6, "title": "失效测试", "media_count": 3}
+        }));
+    });
+    mock_page(
+        server,
+        "6",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [
+                    {"bvid": "BV1ok41117ok", "title": "正常视频", "attr": 0},
+                    {"bvid": "BV1de41117de", "title": "已失效视频", "attr": 9},
+                    {"bvid": "BV1rb41117rb", "title": "地区限制", "attr": 2}
+                ],
+                "has_more": false
+            }
+        }),
+    );
+}
+
+#[tokio::test]
+async fn export_records_invalid_video_status() -> TestResult<()> {
+    let server = MockServer::start();
+    mock_folder_with_invalid_items(&server);
+
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("status.csv");
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=6".to_string(),
+        csv_path: csv_path.clone(),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    };
+
+    let result = export_favlist(options).await?;
+    assert_eq!(result.new_entries.len(), 3);
+    assert_eq!(result.invalid_entries.len(), 2);
+
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    let statuses: Vec<&str> = rows
+        .iter()
+        .map(|row| row.get("status").unwrap().as_str())
+        .collect();
+    assert_eq!(statuses, vec!["available", "deleted", "region_blocked"]);
+
+    let existing = vec!["BV1ok41117ok".to_string()];
+    let separated = classify_missing_videos(&rows, &existing, InvalidVideoPolicy::Separate);
+    assert!(separated.missing.is_empty());
+    assert_eq!(separated.invalid.len(), 2);
+    let included = classify_missing_videos(&rows, &existing, InvalidVideoPolicy::Include);
+    assert_eq!(included.missing.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_separates_invalid_videos_from_csv() -> TestResult<()> {
+    let server = MockServer::start();
+    mock_folder_with_invalid_items(&server);
+
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("separate.csv");
+    let options = ExportOptions {
+        fav_url: "https://space.bilibili.com/1/favlist?fid=6".to_string(),
+        csv_path: csv_path.clone(),
+        base_url: Some(server.base_url()),
+        invalid_policy: InvalidVideoPolicy::Separate,
+        ..Default::default()
+    };
+
+    let result = export_favlist(options).await?;
+    assert_eq!(result.new_entries.len(), 1);
+    let invalid: Vec<VideoStatus> = result
+        .invalid_entries
+        .iter()
+        .map(|entry| entry.status)
+        .collect();
+    assert_eq!(
+        invalid,
+        vec![VideoStatus::Deleted, VideoStatus::RegionBlocked]
+    );
+
+    let existing = load_existing_bv_ids(&csv_path, "utf-8")?;
+    assert_eq!(existing.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn write_entries_follows_unknown_header() -> TestResult<()> {
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("custom.csv");
+    fs::write(
+        &csv_path,
+        "bv_id,备注,title\nBV1xx41117xb,自定义列,旧条目\n",
+    )?;
+
+    let entries = vec![VideoEntry {
+        bv_id: "BV1yy41117xy".to_string(),
+        title: "新条目".to_string(),
+        ..Default::default()
+    }];
+    write_entries(&csv_path, "utf-8", &entries)?;
+
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].get("bv_id").unwrap(), "BV1yy41117xy");
+    assert_eq!(rows[1].get("title").unwrap(), "新条目");
+    assert_eq!(rows[1].get("备注").unwrap(), "");
+
+    Ok(())
+}
+
+#[test]
+fn detect_schema_recognizes_versions() {
+    assert_eq!(
+        detect_schema(&["timestamp", "bv_id", "title", "fav_name"]),
+        CsvSchema::Versioned(1)
+    );
+    assert_eq!(
+        detect_schema(&FIELDNAMES),
+        CsvSchema::Versioned(CSV_SCHEMA_VERSION)
+    );
+    assert_eq!(detect_schema(&["视频BV号", "标题"]), CsvSchema::Legacy);
+    assert_eq!(detect_schema(&["bv_id", "备注"]), CsvSchema::Unknown);
+}
+
+#[test]
+fn legacy_csv_is_migrated_with_backup() -> TestResult<()> {
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("legacy.csv");
+    let original = "BV号,标题\nBV1xx41117xb,旧条目\nBV1de41117de,已失效视频\n";
+    fs::write(&csv_path, original)?;
+
+    let migration = migrate_csv(&csv_path, "utf-8")?.expect("需要迁移");
+    assert_eq!(migration.from_version, 0);
+    assert_eq!(migration.rows, 2);
+    assert_eq!(fs::read_to_string(&migration.backup_path)?, original);
+
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    assert_eq!(rows[0].get("bv_id").unwrap(), "BV1xx41117xb");
+    assert_eq!(rows[0].get("title").unwrap(), "旧条目");
+    assert_eq!(rows[0].get("status").unwrap(), "available");
+    assert_eq!(rows[1].get("status").unwrap(), "deleted");
+
+    assert!(migrate_csv(&csv_path, "utf-8")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn write_entries_migrates_previous_schema() -> TestResult<()> {
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("v1.csv");
+    fs::write(
+        &csv_path,
+        "timestamp,bv_id,title,fav_name\n2025-11-01T00-00-00,BV1xx41117xb,旧条目,收藏夹\n",
+    )?;
+
+    let entries = vec![VideoEntry {
+        bv_id: "BV1yy41117xy".to_string(),
+        title: "新条目".to_string(),
+        fav_title: "收藏夹".to_string(),
+        timestamp: "2025-11-02T00-00-00".to_string(),
+        aid: Some(42),
+        status: VideoStatus::Deleted,
+        ..Default::default()
+    }];
+    write_entries(&csv_path, "utf-8", &entries)?;
+
+    assert!(dir.path().join("v1.v1.bak.csv").exists());
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get("fav_name").unwrap(), "收藏夹");
+    assert_eq!(rows[0].get("aid").unwrap(), "");
+    assert_eq!(rows[1].get("status").unwrap(), "deleted");
+    assert_eq!(rows[1].get("aid").unwrap(), "42");
+
+    Ok(())
+}
+
+#[test]
+fn migrate_csv_keeps_earlier_backups() -> TestResult<()> {
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("v1.csv");
+    let v1 = "timestamp,bv_id,title,fav_name\n2025-11-01T00-00-00,BV1xx41117xb,旧条目,收藏夹\n";
+    fs::write(dir.path().join("v1.v1.bak.csv"), "更早的备份\n")?;
+    fs::write(&csv_path, v1)?;
+
+    let migration = migrate_csv(&csv_path, "utf-8")?.expect("v1 表头应被迁移");
+    assert_eq!(migration.backup_path, dir.path().join("v1.v1.bak-2.csv"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("v1.v1.bak.csv"))?,
+        "更早的备份\n"
+    );
+    assert_eq!(fs::read_to_string(&migration.backup_path)?, v1);
+
+    Ok(())
+}
+
+#[test]
+fn csv_sink_migrates_once_on_open() -> TestResult<()> {
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("v1.csv");
+    fs::write(
+        &csv_path,
+        "timestamp,bv_id,title,fav_name\n2025-11-01T00-00-00,BV1xx41117xb,旧条目,收藏夹\n",
+    )?;
+
+    let mut sink = CsvSink::open(&csv_path, "utf-8")?;
+    assert_eq!(sink.migration().map(|m| m.from_version), Some(1));
+    assert!(sink.load_existing_ids()?.contains("BV1xx41117xb"));
+    for bv_id in ["BV1yy41117xy", "BV1zz41117xz"] {
+        let entry = VideoEntry {
+            bv_id: bv_id.to_string(),
+            title: "新条目".to_string(),
+            fav_title: "收藏夹".to_string(),
+            timestamp: "2025-11-02T00-00-00".to_string(),
+            ..Default::default()
+        };
+        sink.write(std::slice::from_ref(&entry))?;
+    }
+    sink.finish()?;
+
+    assert!(!dir.path().join("v1.v1.bak-2.csv").exists());
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2].get("bv_id").unwrap(), "BV1zz41117xz");
 
     Ok(())
 }
+
+#[tokio::test]
+async fn structured_sinks_dedupe_on_bv_id() -> TestResult<()> {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 8, "title": "结构化", "media_count": 2}
+        }));
+    });
+    mock_page(
+        &server,
+        "8",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [
+                    {"bvid": "BV1js41117js", "title": "甲", "upper": {"mid": 3, "name": "UP"}},
+                    {"bvid": "BV1jl41117jl", "title": "乙"}
+                ],
+                "has_more": false
+            }
+        }),
+    );
+
+    let dir = tempdir()?;
+    for (file, format) in [
+        ("fav.json", ExportFormat::Json),
+        ("fav.jsonl", ExportFormat::Jsonl),
+        ("fav.db", ExportFormat::Sqlite),
+    ] {
+        let path = dir.path().join(file);
+        assert_eq!(ExportFormat::from_path(&path), Some(format));
+        let options = ExportOptions {
+            fav_url: "https://space.bilibili.com/1/favlist?fid=8".to_string(),
+            csv_path: path.clone(),
+            format,
+            base_url: Some(server.base_url()),
+            ..Default::default()
+        };
+
+        let first = export_favlist(options.clone()).await?;
+        assert_eq!(first.new_entries.len(), 2, "{format}");
+        let second = export_favlist(options).await?;
+        assert!(second.new_entries.is_empty(), "{format}");
+
+        match format {
+            ExportFormat::Json => {
+                let entries: Vec<VideoEntry> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                assert_eq!(entries.len(), 2);
+                assert_eq!(entries[0].upper.name, "UP");
+            }
+            ExportFormat::Jsonl => {
+                assert_eq!(fs::read_to_string(&path)?.lines().count(), 2);
+            }
+            _ => {
+                let mut sink = SqliteSink::open(&path)?;
+                let ids = sink.load_existing_ids()?;
+                assert_eq!(ids.len(), 2);
+                assert!(ids.contains("BV1js41117js"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn sink_entry(bv_id: &str) -> VideoEntry {
+    VideoEntry {
+        bv_id: bv_id.to_string(),
+        title: format!("标题 {bv_id}"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn json_sink_appends_pages_in_place() -> TestResult<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("fav.json");
+    let mut sink = JsonSink::new(&path);
+    assert!(sink.load_existing_ids()?.is_empty());
+    sink.write(&[sink_entry("BV1js41117js")])?;
+    // 每页写入后文件都是完整的数组。
+    let entries: Vec<VideoEntry> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    assert_eq!(entries.len(), 1);
+    sink.write(&[sink_entry("BV1jl41117jl"), sink_entry("BV1xx41117xb")])?;
+    sink.write(&[])?;
+    sink.finish()?;
+    let entries: Vec<VideoEntry> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.bv_id.as_str()).collect();
+    assert_eq!(ids, ["BV1js41117js", "BV1jl41117jl", "BV1xx41117xb"]);
+
+    // 重新打开时沿用已有内容继续追加，av号按 BV号去重。
+    fs::write(
+        &path,
+        "[\n  {\"bv_id\": \"av170001\", \"title\": \"旧\"}\n]\n\n",
+    )?;
+    let mut sink = JsonSink::new(&path);
+    let ids = sink.load_existing_ids()?;
+    assert!(ids.contains("BV17x411w7KC"));
+    sink.write(&[sink_entry("BV1js41117js")])?;
+    let values: Vec<serde_json::Value> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    assert_eq!(values.len(), 2);
+
+    let empty = dir.path().join("empty.json");
+    JsonSink::new(&empty).finish()?;
+    assert_eq!(fs::read_to_string(&empty)?, "[]");
+    Ok(())
+}
+
+#[test]
+fn jsonl_sink_repairs_truncated_last_line() -> TestResult<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("fav.jsonl");
+    fs::write(
+        &path,
+        "{\"bv_id\":\"av170001\"}\n{\"bv_id\":\"BV1js41117js\",\"tit",
+    )?;
+    let mut sink = JsonlSink::new(&path);
+    let ids = sink.load_existing_ids()?;
+    assert_eq!(ids.len(), 1);
+    assert!(ids.contains("BV17x411w7KC"));
+    sink.write(&[sink_entry("BV1js41117js")])?;
+    let content = fs::read_to_string(&path)?;
+    assert_eq!(content.lines().count(), 2);
+    for line in content.lines() {
+        serde_json::from_str::<serde_json::Value>(line)?;
+    }
+
+    // 完整但缺换行符的末行保留并补上换行。
+    fs::write(&path, "{\"bv_id\":\"BV1jl41117jl\"}")?;
+    let ids = JsonlSink::new(&path).load_existing_ids()?;
+    assert!(ids.contains("BV1jl41117jl"));
+    assert_eq!(fs::read_to_string(&path)?, "{\"bv_id\":\"BV1jl41117jl\"}\n");
+
+    // 中间行损坏仍然报错。
+    fs::write(&path, "{坏}\n{\"bv_id\":\"BV1jl41117jl\"}\n")?;
+    assert!(JsonlSink::new(&path).load_existing_ids().is_err());
+    Ok(())
+}
+
+#[test]
+fn library_imports_csv_rows_with_their_own_timestamps() -> TestResult<()> {
+    let dir = tempdir()?;
+    let mut library = Library::open(&dir.path().join("library.db"))?;
+    let row = |bv_id: &str, timestamp: &str| {
+        HashMap::from([
+            ("bv_id".to_string(), bv_id.to_string()),
+            ("title".to_string(), format!("标题 {bv_id}")),
+            ("timestamp".to_string(), timestamp.to_string()),
+        ])
+    };
+    let rows = vec![
+        row("BV1aa411c7aa", "2024-03-01T00-00-00"),
+        row("BV1bb411c7bb", "2024-01-01T00-00-00"),
+        row("BV1aa411c7aa", "2024-04-01T00-00-00"),
+        row("BV1cc411c7cc", ""),
+    ];
+    let imported = library.import_csv_rows(1, "默认收藏夹", &rows)?;
+    assert_eq!(
+        imported.added,
+        ["BV1aa411c7aa", "BV1bb411c7bb", "BV1cc411c7cc"]
+    );
+    assert_eq!(imported.seen, 3);
+
+    let first = library.video("BV1aa411c7aa")?.expect("imported video");
+    assert_eq!(first.first_seen, "2024-03-01T00-00-00");
+    let second = library.video("BV1bb411c7bb")?.expect("imported video");
+    assert_eq!(second.first_seen, "2024-01-01T00-00-00");
+    assert_eq!(second.last_seen, "2024-01-01T00-00-00");
+    let undated = library.video("BV1cc411c7cc")?.expect("imported video");
+    assert!(undated.first_seen.as_str() > "2024-04-01T00-00-00");
+    assert_eq!(library.folder_videos(1)?.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn library_tracks_folders_and_local_files() -> TestResult<()> {
+    let dir = tempdir()?;
+    let mut library = Library::open(&dir.path().join("library.db"))?;
+    assert!(!library.has_folder(1)?);
+
+    let mut legacy_row = HashMap::new();
+    legacy_row.insert("bv_id".to_string(), "BV1aa411c7aa".to_string());
+    legacy_row.insert("title".to_string(), "旧条目".to_string());
+    legacy_row.insert("timestamp".to_string(), "2024-01-01T00-00-00".to_string());
+    let imported = library.import_csv_rows(1, "默认收藏夹", &[legacy_row])?;
+    assert_eq!(imported.added, vec!["BV1aa411c7aa".to_string()]);
+    assert!(library.has_folder(1)?);
+
+    let entry = |bv_id: &str, title: &str, status: VideoStatus| VideoEntry {
+        bv_id: bv_id.to_string(),
+        title: title.to_string(),
+        status,
+        ..Default::default()
+    };
+    let sync = library.record_folder(
+        1,
+        "默认收藏夹",
+        &[
+            entry("BV1aa411c7aa", "已失效视频", VideoStatus::Deleted),
+            entry("BV1bb411c7bb", "新条目", VideoStatus::Available),
+        ],
+        "2024-02-01T00-00-00",
+    )?;
+    assert_eq!(sync.added, vec!["BV1bb411c7bb".to_string()]);
+    assert_eq!(sync.seen, 2);
+    let shared = library.record_folder(
+        2,
+        "稍后再看",
+        &[entry("BV1bb411c7bb", "新条目", VideoStatus::Available)],
+        "2024-02-02T00-00-00",
+    )?;
+    assert_eq!(shared.added.len(), 1);
+
+    let old = library.video("BV1aa411c7aa")?.expect("imported video");
+    assert_eq!(old.title, "旧条目");
+    assert_eq!(old.status, VideoStatus::Deleted);
+    assert_eq!(old.first_seen, "2024-01-01T00-00-00");
+    assert_eq!(old.last_seen, "2024-02-01T00-00-00");
+    assert!(!old.is_missing());
+
+    let download_dir = dir.path().join("downloads");
+    let file = download_dir.join("新条目 [BV1bb411c7bb].mp4");
+    let mut mapping = HashMap::new();
+    mapping.insert("BV1bb411c7bb".to_string(), vec![file.clone()]);
+    library.record_local_files(&download_dir, &mapping)?;
+    let shared_video = library.video("BV1bb411c7bb")?.expect("shared video");
+    assert_eq!(shared_video.folders, vec![1, 2]);
+    assert_eq!(shared_video.local_paths, vec![file]);
+    assert_eq!(shared_video.download_state, DownloadState::Downloaded);
+
+    library.record_local_files(&download_dir, &HashMap::new())?;
+    let videos = library.folder_videos(1)?;
+    assert_eq!(videos.len(), 2);
+    let missing: Vec<_> = videos.iter().filter(|video| video.is_missing()).collect();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].bv_id, "BV1bb411c7bb");
+    assert_eq!(missing[0].download_state, DownloadState::Pending);
+
+    assert_eq!(library.remove_from_folder(1, &["BV1bb411c7bb"])?, 1);
+    assert_eq!(library.folder_videos(1)?.len(), 1);
+    let shared_video = library.video("BV1bb411c7bb")?.expect("still in folder 2");
+    assert_eq!(shared_video.folders, vec![2]);
+
+    Ok(())
+}
+
+#[test]
+fn diff_changes_reports_full_change_set() {
+    let row = |bv_id: &str, title: &str, status: &str| {
+        let mut row = HashMap::new();
+        row.insert("bv_id".to_string(), bv_id.to_string());
+        row.insert("title".to_string(), title.to_string());
+        row.insert("status".to_string(), status.to_string());
+        row
+    };
+    let old_rows = vec![
+        row("BV1kept411aaa", "保持", "available"),
+        row("BV1gone411bbb", "被移除", "available"),
+        row("BV1name411ccc", "旧标题", "available"),
+        row("BV1dead411ddd", "将失效", "available"),
+    ];
+    let new_rows = vec![
+        row("BV1new4111eee", "新增", "available"),
+        row("BV1kept411aaa", "保持", "available"),
+        row("BV1name411ccc", "新标题", "available"),
+        row("BV1dead411ddd", "已失效视频", "deleted"),
+    ];
+
+    let changes = diff_changes(&old_rows, &new_rows);
+    assert!(!changes.is_empty());
+    assert_eq!(changes.added.len(), 1);
+    assert_eq!(changes.added[0]["bv_id"], "BV1new4111eee");
+    assert_eq!(changes.removed.len(), 1);
+    assert_eq!(changes.removed[0]["title"], "被移除");
+    assert_eq!(changes.title_changed.len(), 1);
+    assert_eq!(changes.title_changed[0].old_title, "旧标题");
+    assert_eq!(changes.title_changed[0].new_title, "新标题");
+    assert_eq!(changes.became_invalid.len(), 1);
+    assert_eq!(changes.became_invalid[0]["bv_id"], "BV1dead411ddd");
+
+    assert!(diff_changes(&new_rows, &new_rows).is_empty());
+}
+
+#[test]
+fn parse_fav_source_accepts_space_urls() {
+    assert_eq!(
+        parse_fav_source("https://space.bilibili.com/234561771/favlist").unwrap(),
+        FavSource::UserFolders { up_mid: 234561771 }
+    );
+    assert_eq!(
+        parse_fav_source("https://space.bilibili.com/234561771/favlist/?spm_id_from=333.1007")
+            .unwrap(),
+        FavSource::UserFolders { up_mid: 234561771 }
+    );
+    assert_eq!(
+        parse_fav_source("https://space.bilibili.com/234561771/favlist?fid=3670113371").unwrap(),
+        FavSource::Folder {
+            media_id: 3670113371
+        }
+    );
+    assert!(parse_fav_source("https://space.bilibili.com/234561771/video").is_err());
+    assert!(parse_fav_source("https://space.bilibili.com/234561771/favlist?fid=abc").is_err());
+}
+
+#[tokio::test]
+async fn export_all_folders_from_space_url() -> TestResult<()> {
+    let server = MockServer::start();
+
+    let list_mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/folder/created/list-all")
+            .query_param("up_mid", "42");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "count": 2,
+                "list": [
+                    {"id": 101, "fid": 1, "mid": 42, "title": "默认收藏夹", "media_count": 1},
+                    {"id": 202, "fid": 2, "mid": 42, "title": "新建收藏夹", "media_count": 1}
+                ]
+            }
+        }));
+    });
+    for (media_id, title, bvid) in [
+        ("101", "默认收藏夹", "BV1xx411c7aa"),
+        ("202", "新建收藏夹", "BV1xx411c7bb"),
+    ] {
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/x/v3/fav/folder/info")
+                .query_param("media_id", media_id);
+            then.status(200).json_body(json!({
+                "code": 0,
+                "data": {"id": media_id.parse::<i64>().unwrap(), "title": title, "media_count": 1}
+            }));
+        });
+        mock_page(
+            &server,
+            media_id,
+            "1",
+            json!({
+                "code": 0,
+                "data": {"medias": [{"bvid": bvid, "title": title}], "has_more": false}
+            }),
+        );
+    }
+
+    let dir = tempdir()?;
+    let base = dir.path().join("all.csv");
+    let results = export_all_folders(ExportOptions {
+        fav_url: "https://space.bilibili.com/42/favlist".to_string(),
+        csv_path: base.clone(),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+
+    list_mock.assert();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].folder_info.title, "默认收藏夹");
+    assert_eq!(results[0].csv_path, dir.path().join("all-101.csv"));
+    assert_eq!(results[1].csv_path, folder_output_path(&base, 202));
+    let rows = read_csv_rows(&results[1].csv_path, "utf-8")?;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["bv_id"], "BV1xx411c7bb");
+
+    // 指向用户空间的短链接只解析一次，列出与导出共用同一客户端。
+    let short = server.mock(|when, then| {
+        when.method(GET).path("/SpAcE1");
+        then.status(302)
+            .header("location", "https://space.bilibili.com/42/favlist");
+    });
+    let results = export_all_folders(ExportOptions {
+        fav_url: "https://b23.tv/SpAcE1".to_string(),
+        csv_path: dir.path().join("short.csv"),
+        base_url: Some(server.base_url()),
+        short_link_base: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(results.len(), 2);
+    short.assert_hits(1);
+    list_mock.assert_hits(2);
+
+    Ok(())
+}
+
+#[test]
+fn parse_fav_source_recognizes_seasons_and_series() {
+    let cases = [
+        (
+            "https://space.bilibili.com/42/favlist?fid=777&ftype=collect&ctype=21",
+            FavSource::CollectedSeason { season_id: 777 },
+        ),
+        (
+            "https://space.bilibili.com/42/favlist?fid=888&ftype=collect&ctype=11",
+            FavSource::Folder { media_id: 888 },
+        ),
+        (
+            "https://space.bilibili.com/42/channel/collectiondetail?sid=123",
+            FavSource::Season {
+                mid: 42,
+                season_id: 123,
+            },
+        ),
+        (
+            "https://space.bilibili.com/42/channel/seriesdetail?sid=456",
+            FavSource::Series {
+                mid: 42,
+                series_id: 456,
+            },
+        ),
+        (
+            "https://space.bilibili.com/42/lists/123?type=season",
+            FavSource::Season {
+                mid: 42,
+                season_id: 123,
+            },
+        ),
+        (
+            "https://space.bilibili.com/42/lists/456?type=series",
+            FavSource::Series {
+                mid: 42,
+                series_id: 456,
+            },
+        ),
+    ];
+    for (url, expected) in cases {
+        assert_eq!(parse_fav_source(url).unwrap(), expected, "{url}");
+        assert_eq!(parse_media_id(url).unwrap(), expected.list_id().unwrap());
+    }
+    assert!(parse_fav_source("https://space.bilibili.com/42/channel/seriesdetail").is_err());
+}
+
+#[tokio::test]
+async fn export_season_and_series_as_video_entries() -> TestResult<()> {
+    let server = MockServer::start();
+    let archive = |bvid: &str, title: &str| {
+        json!({
+            "aid": 1, "bvid": bvid, "title": title, "pic": "cover.jpg",
+            "duration": 60, "pubdate": 1700000000, "ctime": 1700000000,
+            "stat": {"view": 10, "danmaku": 2, "favorite": 3}
+        })
+    };
+
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/polymer/web-space/seasons_archives_list")
+            .query_param("mid", "42")
+            .query_param("season_id", "123")
+            .query_param("page_num", "1");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "archives": [archive("BV1se411c7aa", "合集一")],
+                "meta": {"season_id": 123, "mid": 42, "name": "我的合集", "total": 2},
+                "page": {"page_num": 1, "page_size": 1, "total": 2}
+            }
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/polymer/web-space/seasons_archives_list")
+            .query_param("season_id", "123")
+            .query_param("page_num", "2");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "archives": [archive("BV1se411c7bb", "合集二")],
+                "meta": {"season_id": 123, "mid": 42, "name": "我的合集", "total": 2},
+                "page": {"page_num": 2, "page_size": 1, "total": 2}
+            }
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/series/series")
+            .query_param("series_id", "456");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"meta": {"series_id": 456, "mid": 42, "name": "我的系列", "total": 1}}
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/series/archives")
+            .query_param("series_id", "456")
+            .query_param("pn", "1");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "archives": [archive("BV1sr411c7cc", "系列一")],
+                "page": {"num": 1, "size": 1, "total": 1}
+            }
+        }));
+    });
+
+    let dir = tempdir()?;
+    let season = export_favlist(ExportOptions {
+        fav_url: "https://space.bilibili.com/42/channel/collectiondetail?sid=123".to_string(),
+        csv_path: dir.path().join("season.csv"),
+        page_size: 1,
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(season.folder_info.title, "我的合集");
+    assert_eq!(season.total_count, Some(2));
+    let bvids: Vec<_> = season
+        .new_entries
+        .iter()
+        .map(|e| e.bv_id.as_str())
+        .collect();
+    assert_eq!(bvids, ["BV1se411c7aa", "BV1se411c7bb"]);
+    let first = &season.new_entries[0];
+    assert_eq!(first.fav_title, "我的合集");
+    assert_eq!(first.upper.mid, 42);
+    assert_eq!(first.stats.play, 10);
+    assert_eq!(first.pubtime, 1700000000);
+
+    let series = export_favlist(ExportOptions {
+        fav_url: "https://space.bilibili.com/42/lists/456?type=series".to_string(),
+        csv_path: dir.path().join("series.csv"),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(series.folder_info.title, "我的系列");
+    assert_eq!(series.new_entries.len(), 1);
+    assert_eq!(series.new_entries[0].bv_id, "BV1sr411c7cc");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_watch_later_requires_cookie() -> TestResult<()> {
+    let server = MockServer::start();
+    let toview = server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v2/history/toview")
+            .header("cookie", "SESSDATA=abc");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "count": 2,
+                "list": [
+                    {"aid": 1, "bvid": "BV1wl411c7aa", "title": "稍后一", "add_at": 1700000100,
+                     "videos": 2, "owner": {"mid": 7, "name": "UP"}, "stat": {"view": 5}},
+                    {"aid": 2, "bvid": "BV1wl411c7bb", "title": "稍后二", "add_at": 1700000000}
+                ]
+            }
+        }));
+    });
+
+    assert_eq!(
+        parse_fav_source("https://www.bilibili.com/watchlater/#/list")?,
+        FavSource::WatchLater
+    );
+    assert_eq!(
+        parse_fav_source("https://www.bilibili.com/list/watchlater?bvid=BV1wl411c7aa")?,
+        FavSource::WatchLater
+    );
+    assert_eq!(
+        parse_fav_source("https://www.bilibili.com/account/history")?,
+        FavSource::History
+    );
+
+    let dir = tempdir()?;
+    let options = ExportOptions {
+        fav_url: "https://www.bilibili.com/watchlater/#/list".to_string(),
+        csv_path: dir.path().join("watchlater.csv"),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    };
+    let err = export_favlist(options.clone()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ExportError::Core(FavlistError::CookieRequired(_))
+    ));
+    toview.assert_hits(0);
+
+    let result = export_favlist(ExportOptions {
+        cookie: Some("SESSDATA=abc".to_string()),
+        ..options
+    })
+    .await?;
+    assert_eq!(result.folder_info.title, "稍后再看");
+    assert_eq!(result.folder_info.media_id, WATCH_LATER_LIST_ID);
+    assert_eq!(result.new_entries.len(), 2);
+    let first = &result.new_entries[0];
+    assert_eq!(first.fav_time, 1700000100);
+    assert_eq!(first.page_count, 2);
+    assert_eq!(first.upper.name, "UP");
+    assert_eq!(result.latest_fav_time, Some(1700000100));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_history_follows_cursor() -> TestResult<()> {
+    let server = MockServer::start();
+    let item = |bvid: &str, view_at: i64, business: &str| {
+        json!({
+            "title": bvid, "view_at": view_at, "author_mid": 9, "author_name": "作者",
+            "history": {"oid": view_at, "bvid": bvid, "business": business}
+        })
+    };
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/web-interface/history/cursor")
+            .query_param("max", "0")
+            .query_param("view_at", "0");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "cursor": {"max": 200, "view_at": 1700000200},
+                "list": [item("BV1hi411c7aa", 1700000300, "archive"), item("", 1700000250, "live")]
+            }
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/web-interface/history/cursor")
+            .query_param("max", "200")
+            .query_param("view_at", "1700000200");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "cursor": {"max": 100, "view_at": 1700000100},
+                "list": [item("BV1hi411c7bb", 1700000200, "archive")]
+            }
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/web-interface/history/cursor")
+            .query_param("max", "100");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"cursor": {"max": 0, "view_at": 0}, "list": []}
+        }));
+    });
+
+    let dir = tempdir()?;
+    let result = export_favlist(ExportOptions {
+        fav_url: "https://www.bilibili.com/account/history".to_string(),
+        csv_path: dir.path().join("history.csv"),
+        cookie: Some("SESSDATA=abc".to_string()),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+    let bvids: Vec<_> = result
+        .new_entries
+        .iter()
+        .map(|e| e.bv_id.as_str())
+        .collect();
+    assert_eq!(bvids, ["BV1hi411c7aa", "BV1hi411c7bb"]);
+    assert_eq!(result.new_entries[0].upper.name, "作者");
+
+    Ok(())
+}
+
+#[test]
+fn parse_source_ref_accepts_ids_ml_and_mobile_links() -> TestResult<()> {
+    let folder = FavSource::Folder {
+        media_id: 3670113371,
+    };
+    for input in [
+        "3670113371",
+        " ml3670113371 ",
+        "https://www.bilibili.com/medialist/detail/ml3670113371",
+        "www.bilibili.com/medialist/play/ml3670113371?spm_id_from=333.999",
+        "https://www.bilibili.com/list/ml3670113371",
+        "https://m.bilibili.com/space/234561771/favlist?fid=3670113371",
+    ] {
+        assert_eq!(parse_fav_source(input)?, folder, "{input}");
+        assert_eq!(parse_media_id(input)?, 3670113371, "{input}");
+    }
+    assert_eq!(
+        parse_fav_source("https://m.bilibili.com/space/234561771/favlist")?,
+        FavSource::UserFolders { up_mid: 234561771 }
+    );
+    assert_eq!(parse_source_ref("42")?, SourceRef::MediaId(42));
+    assert!(matches!(
+        parse_source_ref("https://b23.tv/AbCdEf")?,
+        SourceRef::ShortLink(_)
+    ));
+
+    let sources = [
+        folder,
+        FavSource::UserFolders { up_mid: 7 },
+        FavSource::CollectedSeason { season_id: 8 },
+        FavSource::Season {
+            mid: 7,
+            season_id: 9,
+        },
+        FavSource::Series {
+            mid: 7,
+            series_id: 10,
+        },
+        FavSource::WatchLater,
+        FavSource::History,
+    ];
+    for source in sources {
+        assert_eq!(parse_fav_source(&source.canonical_url())?, source);
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_source_ref_errors_name_the_form() {
+    let cases = [
+        ("mlabc", "ml号"),
+        ("https://m.bilibili.com/space/42/video", "移动端链接"),
+        ("https://www.bilibili.com/medialist/detail/mlx1", "网页链接"),
+        ("https://b23.tv/AbCdEf", "短链接"),
+        ("   ", "输入为空"),
+    ];
+    for (input, expected) in cases {
+        let err = parse_fav_source(input).unwrap_err();
+        assert!(
+            matches!(err, FavlistError::InvalidSource { .. }),
+            "{input}: {err}"
+        );
+        assert!(err.to_string().contains(expected), "{input}: {err}");
+    }
+    let err = parse_fav_source("ml99999999999999999999").unwrap_err();
+    assert!(err.to_string().contains("数值无效"), "{err}");
+}
+
+#[tokio::test]
+async fn export_resolves_short_links_through_client() -> TestResult<()> {
+    let server = MockServer::start();
+    let short = server.mock(|when, then| {
+        when.method(GET).path("/AbCdEf");
+        then.status(302).header(
+            "location",
+            "https://m.bilibili.com/space/5/favlist?fid=77&spm_id_from=333.999",
+        );
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/BadOne");
+        then.status(302)
+            .header("location", "https://www.bilibili.com/video/BV1aa41117aa");
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 77, "title": "短链收藏夹", "media_count": 1}
+        }));
+    });
+    mock_page(
+        &server,
+        "77",
+        "1",
+        json!({
+            "code": 0,
+            "data": {
+                "medias": [{"bvid": "BV1aa41117aa", "title": "短链视频"}],
+                "has_more": false
+            }
+        }),
+    );
+
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        short_link_base: Some(server.base_url()),
+        ..Default::default()
+    })?;
+    assert_eq!(
+        client.resolve_source("b23.tv/AbCdEf").await?,
+        FavSource::Folder { media_id: 77 }
+    );
+    let err = client
+        .resolve_source("https://b23.tv/BadOne")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, FavlistError::InvalidSource { .. }));
+    assert!(err.to_string().contains("短链接"), "{err}");
+    assert!(err.to_string().contains("BV1aa41117aa"), "{err}");
+
+    let dir = tempdir()?;
+    let results = export_all_folders(ExportOptions {
+        fav_url: "https://b23.tv/AbCdEf".to_string(),
+        csv_path: dir.path().join("short.csv"),
+        base_url: Some(server.base_url()),
+        short_link_base: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].folder_info.title, "短链收藏夹");
+    assert_eq!(results[0].new_entries[0].bv_id, "BV1aa41117aa");
+    short.assert_hits(2);
+
+    Ok(())
+}
+
+#[test]
+fn av_and_bv_ids_convert_both_ways() -> TestResult<()> {
+    for (aid, bvid) in [(170001, "BV17x411w7KC"), (111298867365120, "BV1L9Uoa9EUx")] {
+        assert_eq!(av_to_bv(aid)?, bvid);
+        assert_eq!(bv_to_av(bvid)?, aid);
+        assert_eq!(
+            normalize_video_id(&format!("AV{aid}")).as_deref(),
+            Some(bvid)
+        );
+    }
+    assert_eq!(
+        normalize_video_id(" bv17x411w7KC ").as_deref(),
+        Some("BV17x411w7KC")
+    );
+    assert_eq!(normalize_video_id("av"), None);
+    assert_eq!(normalize_video_id("BV17x411w7"), None);
+    assert!(matches!(av_to_bv(0), Err(FavlistError::InvalidVideoId(_))));
+    assert!(av_to_bv(1 << 51).is_err());
+    assert!(bv_to_av("BV17x411w7K0").is_err());
+    Ok(())
+}
+
+#[test]
+fn av_ids_are_normalized_in_scan_and_diff() -> TestResult<()> {
+    let ids = extract_bvids("[av170001] 标题 BV1L9Uoa9EUx Java123.mp4");
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains("BV17x411w7KC"));
+    assert!(ids.contains("BV1L9Uoa9EUx"));
+    // 编码标签与过短、后接字母的 av 号不计入。
+    for name in [
+        "[AV1] 标题.mp4",
+        "标题.av1.mkv",
+        "标题-AV1-1080p.mp4",
+        "标题 av12.mp4",
+        "标题 av170001x.mp4",
+        "标题 AV170001.mp4",
+    ] {
+        assert!(extract_bvids(name).is_empty(), "{name}");
+    }
+    assert_eq!(
+        extract_bvids("[AV1] av170001_av170001.mp4"),
+        HashSet::from(["BV17x411w7KC".to_string()])
+    );
+
+    let dir = tempdir()?;
+    fs::write(dir.path().join("av170001.mp4"), b"")?;
+    let archive_dir = dir.path().join(REMOVED_ARCHIVE_DIR);
+    fs::create_dir_all(&archive_dir)?;
+    fs::write(archive_dir.join("BV1L9Uoa9EUx.mp4"), b"")?;
+    let mapping = scan_directory_bvids(dir.path())?;
+    assert!(mapping.contains_key("BV17x411w7KC"));
+    assert!(!mapping.contains_key("BV1L9Uoa9EUx"));
+
+    let row = |bv_id: &str| HashMap::from([("bv_id".to_string(), bv_id.to_string())]);
+    assert_eq!(row_bvid(&row("av170001")).as_deref(), Some("BV17x411w7KC"));
+    let changes = diff_changes(&[row("av170001")], &[row("BV17x411w7KC")]);
+    assert!(changes.is_empty());
+
+    let item: VideoItem = serde_json::from_value(json!({"id": 170001, "title": "仅有aid"}))?;
+    assert_eq!(item.resolve_bvid().as_deref(), Some("BV17x411w7KC"));
+    Ok(())
+}
+
+#[test]
+fn cookie_files_are_parsed_by_format() -> TestResult<()> {
+    let netscape = "# Netscape HTTP Cookie File\n\
+        .bilibili.com\tTRUE\t/\tFALSE\t4102444800\tSESSDATA\tabc%2C123\n\
+        #HttpOnly_.bilibili.com\tTRUE\t/\tTRUE\t0\tbili_jct\tcsrf\n\
+        .bilibili.com\tTRUE\t/\tFALSE\t1000\tDedeUserID\texpired\n\
+        .example.com\tTRUE\t/\tFALSE\t0\tother\tx\n";
+    assert_eq!(
+        CookieFileFormat::detect(netscape),
+        CookieFileFormat::Netscape
+    );
+    assert_eq!(
+        parse_netscape_cookies(netscape, 1_700_000_000),
+        "SESSDATA=abc%2C123; bili_jct=csrf"
+    );
+
+    let bbdown = "DedeUserID=42;DedeUserID__ckMd5=md5;Expires=1700000000;SESSDATA=abc%2C123;bili_jct=csrf;gourl=https%3A%2F%2Fwww.bilibili.com;first_domain=.bilibili.com\n";
+    assert_eq!(CookieFileFormat::detect(bbdown), CookieFileFormat::BBDown);
+    assert_eq!(
+        parse_bbdown_data(bbdown),
+        "DedeUserID=42; DedeUserID__ckMd5=md5; SESSDATA=abc%2C123; bili_jct=csrf"
+    );
+
+    let dir = tempdir()?;
+    let empty = dir.path().join("cookies.txt");
+    fs::write(&empty, "# Netscape HTTP Cookie File\n")?;
+    assert!(matches!(
+        load_cookie_file(&empty),
+        Err(FavlistError::CookieExpired(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn cookie_file_is_sent_and_expiry_is_reported() -> TestResult<()> {
+    let server = MockServer::start();
+    let valid = server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/folder/info")
+            .header("cookie", "SESSDATA=fresh; bili_jct=csrf");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 88, "title": "私密收藏夹", "media_count": 0}
+        }));
+    });
+    server.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/folder/info")
+            .header("cookie", "SESSDATA=stale");
+        then.status(200)
+            .json_body(json!({"code": -101, "message": "账号未登录"}));
+    });
+
+    let dir = tempdir()?;
+    let cookie_file = dir.path().join("BBDown.data");
+    fs::write(&cookie_file, "SESSDATA=fresh;bili_jct=csrf;gourl=x")?;
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        cookie_file: Some(cookie_file.clone()),
+        ..Default::default()
+    })?;
+    assert_eq!(client.get_folder_info(88).await?.title, "私密收藏夹");
+    valid.assert();
+
+    fs::write(&cookie_file, "SESSDATA=stale")?;
+    let err = export_favlist(ExportOptions {
+        fav_url: "88".to_string(),
+        csv_path: dir.path().join("private.csv"),
+        cookie_file: Some(cookie_file),
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err, ExportError::Core(FavlistError::CookieExpired(_))),
+        "{err}"
+    );
+    assert!(err.to_string().contains("Cookie已失效"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn proxy_config_validates_scheme() {
+    assert!(ProxyConfig::new("http://127.0.0.1:7890").to_proxy().is_ok());
+    assert!(ProxyConfig::new("socks5h://127.0.0.1:1080")
+        .with_auth("user", "pass")
+        .with_no_proxy(["localhost"])
+        .to_proxy()
+        .is_ok());
+    let err = ProxyConfig::new("ftp://127.0.0.1:21")
+        .to_proxy()
+        .unwrap_err();
+    assert!(matches!(err, FavlistError::InvalidUrl(_)), "{err}");
+    assert!(ProxyConfig::new("not a url").to_proxy().is_err());
+    assert!(BiliFavClient::new(ClientOptions {
+        proxy: Some(ProxyConfig::new("gopher://proxy")),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[tokio::test]
+async fn client_requests_go_through_proxy() -> TestResult<()> {
+    // 经 HTTP 代理时请求行为绝对 URI，目标主机不可解析也能由代理收到。
+    let proxy = MockServer::start();
+    let forwarded = proxy.mock(|when, then| {
+        when.method(GET)
+            .path("/x/v3/fav/folder/info")
+            .header_exists("proxy-authorization");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 7, "title": "代理收藏夹", "media_count": 0}
+        }));
+    });
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some("http://api.bilibili.invalid".to_string()),
+        proxy: Some(ProxyConfig::new(proxy.base_url()).with_auth("user", "pass")),
+        ..Default::default()
+    })?;
+    assert_eq!(client.get_folder_info(7).await?.title, "代理收藏夹");
+    forwarded.assert();
+    Ok(())
+}
+
+#[tokio::test]
+async fn fixtures_record_then_replay_offline() -> TestResult<()> {
+    let server = MockServer::start();
+    let info = server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/folder/info");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"id": 55, "title": "录制收藏夹", "media_count": 1}
+        }));
+    });
+    let list = server.mock(|when, then| {
+        when.method(GET).path("/x/v3/fav/resource/list");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {
+                "medias": [{"bvid": "BV17x411w7KC", "title": "录制视频", "id": 170001}],
+                "has_more": false
+            }
+        }));
+    });
+
+    let dir = tempdir()?;
+    let fixture_dir = dir.path().join("fixtures");
+    let recorded = export_favlist(ExportOptions {
+        fav_url: "55".to_string(),
+        csv_path: dir.path().join("recorded.csv"),
+        base_url: Some(server.base_url()),
+        fixtures: Some(FixtureMode::Record(fixture_dir.clone())),
+        ..Default::default()
+    })
+    .await?;
+    info.assert_hits(1);
+    list.assert_hits(1);
+    let info_file = fixture_dir.join("x_v3_fav_folder_info__media_id_55.json");
+    let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&info_file)?)?;
+    assert_eq!(saved["status"], 200);
+    assert_eq!(saved["body"]["data"]["title"], "录制收藏夹");
+
+    // 回放时指向不可达的地址，确认没有发出网络请求。
+    let replay_options = ExportOptions {
+        fav_url: "55".to_string(),
+        csv_path: dir.path().join("replayed.csv"),
+        base_url: Some("http://127.0.0.1:9".to_string()),
+        fixtures: Some(FixtureMode::Replay(fixture_dir.clone())),
+        ..Default::default()
+    };
+    let replayed = export_favlist(replay_options.clone()).await?;
+    assert_eq!(replayed.folder_info.title, recorded.folder_info.title);
+    assert_eq!(replayed.new_entries, recorded.new_entries);
+    info.assert_hits(1);
+
+    fs::remove_file(&info_file)?;
+    let err = export_favlist(replay_options).await.unwrap_err();
+    assert!(
+        matches!(err, ExportError::Core(FavlistError::Fixture(_))),
+        "{err}"
+    );
+    assert!(err.to_string().contains("media_id_55"), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn replay_checked_in_fixture() -> TestResult<()> {
+    let dir = tempdir()?;
+    let result = export_favlist(ExportOptions {
+        fav_url: "https://space.bilibili.com/234561771/favlist?fid=3670113371".to_string(),
+        csv_path: dir.path().join("demo.csv"),
+        page_size: 20,
+        fixtures: Some(FixtureMode::Replay(
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/favlist_demo"),
+        )),
+        ..Default::default()
+    })
+    .await?;
+    assert_eq!(result.folder_info.title, "回放示例收藏夹");
+    assert_eq!(result.total_count, Some(3));
+    let ids: Vec<&str> = result
+        .new_entries
+        .iter()
+        .map(|entry| entry.bv_id.as_str())
+        .collect();
+    assert_eq!(ids, ["BV17x411w7KC", "BV1Q541167Qg", "BV1mK4y1C7Bz"]);
+    assert_eq!(result.new_entries[0].upper.name, "UP主甲");
+    assert_eq!(result.new_entries[1].page_count, 3);
+    assert_eq!(result.new_entries[2].status, VideoStatus::Deleted);
+    Ok(())
+}
+
+const FAKE_SCENARIO: &str = r#"
+folders:
+  - id: 1001
+    title: 模拟收藏夹
+    mid: 42
+    videos:
+      - {bvid: BV17x411w7KC, title: 视频一}
+      - {bvid: BV1Q541167Qg, title: 视频二, pages: 3}
+      - {bvid: BV1mK4y1C7Bz, title: 视频三, invalid: deleted}
+      - {aid: 170001, title: 只有av号}
+      - {bvid: BV1xx41117xb, title: 视频五, invalid: restricted}
+    errors:
+      - {endpoint: list, page: 2, code: -352, message: 风控校验失败, times: 1}
+  - id: 1002
+    title: 私密收藏夹
+    errors:
+      - {code: -403, message: 访问权限不足}
+  - id: 1003
+    title: 慢速收藏夹
+    latency_ms: 500
+"#;
+
+#[tokio::test]
+async fn fake_api_server_serves_scenario() -> TestResult<()> {
+    let server = FakeServer::start(Scenario::from_yaml(FAKE_SCENARIO)?, "127.0.0.1:0")?;
+    let dir = tempdir()?;
+    let options = ExportOptions {
+        fav_url: "1001".to_string(),
+        csv_path: dir.path().join("fake.csv"),
+        page_size: 2,
+        base_url: Some(server.base_url()),
+        retry: RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            jitter: false,
+            ..RetryPolicy::default()
+        },
+        ..Default::default()
+    };
+    let result = export_favlist(options.clone()).await?;
+    assert_eq!(result.folder_info.title, "模拟收藏夹");
+    assert_eq!(result.total_count, Some(5));
+    assert_eq!(result.retry_count, 1);
+    let statuses: Vec<(String, VideoStatus)> = result
+        .new_entries
+        .iter()
+        .map(|entry| (entry.bv_id.clone(), entry.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("BV17x411w7KC".to_string(), VideoStatus::Available),
+            ("BV1Q541167Qg".to_string(), VideoStatus::Available),
+            ("BV1mK4y1C7Bz".to_string(), VideoStatus::Deleted),
+            // 只有 aid 的条目换算为 BV 号后与第一条重复，被去重
+            ("BV1xx41117xb".to_string(), VideoStatus::RegionBlocked),
+        ]
+    );
+    // 1 次信息 + 3 页列表 + 1 次被注入的风控错误
+    assert_eq!(server.request_count(), 5);
+
+    let err = export_favlist(ExportOptions {
+        fav_url: "1002".to_string(),
+        csv_path: dir.path().join("private.csv"),
+        ..options.clone()
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err, ExportError::Core(FavlistError::Api { code: -403, .. })),
+        "{err}"
+    );
+
+    let slow = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        timeout: Duration::from_millis(100),
+        retry: RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        },
+        ..Default::default()
+    })?;
+    let err = slow.get_folder_info(1003).await.unwrap_err();
+    assert!(matches!(err, FavlistError::Request(_)), "{err}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn sync_folders_runs_jobs_with_bounded_concurrency() -> TestResult<()> {
+    let mut scenario = String::from("latency_ms: 30\nfolders:\n");
+    for id in 1..=5 {
+        scenario.push_str(&format!(
+            "  - id: {id}\n    title: 收藏夹{id}\n    videos: [{{bvid: BV1xx41117x{id}, title: 视频{id}}}]\n"
+        ));
+    }
+    scenario.push_str(
+        "  - id: 6\n    title: 坏收藏夹\n    errors: [{code: -403, message: 访问权限不足}]\n",
+    );
+    let server = FakeServer::start(Scenario::from_yaml(&scenario)?, "127.0.0.1:0")?;
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })?;
+
+    let dir = tempdir()?;
+    let jobs: Vec<SyncJob> = (1..=6)
+        .map(|id| {
+            SyncJob::new(
+                format!("收藏夹{id}"),
+                ExportOptions {
+                    fav_url: id.to_string(),
+                    csv_path: dir.path().join(format!("{id}.csv")),
+                    // 客户端设置以共享客户端为准
+                    base_url: Some("http://127.0.0.1:9".to_string()),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+
+    let running = Arc::new(Mutex::new((0usize, 0usize)));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (running_cb, events_cb) = (Arc::clone(&running), Arc::clone(&events));
+    let report = sync_folders(
+        &client,
+        jobs,
+        SyncOptions {
+            max_concurrency: 2,
+            callback: Some(Arc::new(move |event: SyncEvent| {
+                let mut running = running_cb.lock().unwrap();
+                match &event {
+                    SyncEvent::Started { .. } => {
+                        running.0 += 1;
+                        running.1 = running.1.max(running.0);
+                    }
+                    SyncEvent::Finished { .. } => running.0 -= 1,
+                    SyncEvent::Progress { .. } => {}
+                }
+                events_cb.lock().unwrap().push(event);
+            })),
+        },
+    )
+    .await;
+
+    assert_eq!(running.lock().unwrap().1, 2);
+    assert_eq!(report.outcomes.len(), 6);
+    assert_eq!(report.success_count(), 5);
+    assert_eq!(report.new_entry_count(), 5);
+    let names: Vec<&str> = report.succeeded().map(|(name, _)| name).collect();
+    assert_eq!(
+        names,
+        ["收藏夹1", "收藏夹2", "收藏夹3", "收藏夹4", "收藏夹5"]
+    );
+    let failures: Vec<String> = report
+        .failed()
+        .map(|(name, err)| format!("{name}: {err}"))
+        .collect();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with("收藏夹6") && failures[0].contains("-403"));
+    assert!(fs::read_to_string(dir.path().join("3.csv"))?.contains("BV1xx41117x3"));
+
+    let events = events.lock().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, SyncEvent::Progress { index: 3, .. })));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        SyncEvent::Finished {
+            index: 5,
+            error: Some(_),
+            ..
+        }
+    )));
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_cancellation_flushes_written_pages() -> TestResult<()> {
+    let scenario = Scenario::from_yaml(
+        r#"
+folders:
+  - id: 7
+    title: 可取消收藏夹
+    videos:
+      - {bvid: BV17x411w7KC, title: 视频一}
+      - {bvid: BV1Q541167Qg, title: 视频二}
+      - {bvid: BV1mK4y1C7Bz, title: 视频三}
+      - {bvid: BV1xx41117xb, title: 视频四}
+      - {bvid: BV1yy41117xy, title: 视频五}
+"#,
+    )?;
+    let server = FakeServer::start(scenario, "127.0.0.1:0")?;
+    let dir = tempdir()?;
+    let csv_path = dir.path().join("cancel.csv");
+    let cancel = CancellationToken::new();
+    let trigger = cancel.clone();
+    let err = export_favlist(ExportOptions {
+        fav_url: "7".to_string(),
+        csv_path: csv_path.clone(),
+        page_size: 2,
+        base_url: Some(server.base_url()),
+        cancel: Some(cancel),
+        // 处理完第一页后取消，第二页不再请求
+        event_callback: Some(Arc::new(move |event: ExportEvent| {
+            if let ExportEvent::PageFetched { processed, .. } = event {
+                if processed >= 2 {
+                    trigger.cancel();
+                }
+            }
+        })),
+        ..Default::default()
+    })
+    .await
+    .unwrap_err();
+    assert!(err.is_cancelled(), "{err}");
+    assert!(
+        matches!(&err, ExportError::Cancelled { written: 2, path } if *path == csv_path),
+        "{err}"
+    );
+    assert_eq!(server.request_count(), 2);
+    let rows = read_csv_rows(&csv_path, "utf-8")?;
+    assert_eq!(rows.len(), 2);
+
+    // 已取消的令牌不会发出任何请求，同步任务也随之全部取消。
+    let cancelled = CancellationToken::new();
+    cancelled.cancel();
+    let client = BiliFavClient::new(ClientOptions {
+        base_url: Some(server.base_url()),
+        ..Default::default()
+    })?;
+    let jobs = (0..3)
+        .map(|index| {
+            SyncJob::new(
+                format!("任务{index}"),
+                ExportOptions {
+                    fav_url: "7".to_string(),
+                    csv_path: dir.path().join(format!("sync-{index}.csv")),
+                    cancel: Some(cancelled.clone()),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    let report = sync_folders(&client, jobs, SyncOptions::default()).await;
+    assert_eq!(report.failure_count(), 3);
+    assert!(report.failed().all(|(_, err)| err.is_cancelled()));
+    assert_eq!(server.request_count(), 2);
+    Ok(())
+}
+
+fn login_options(server: &MockServer) -> LoginOptions {
+    LoginOptions {
+        passport_base: Some(server.base_url()),
+        www_base: Some(server.base_url()),
+        poll_interval: Duration::from_millis(10),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn qr_login_polls_until_confirmed() -> TestResult<()> {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/x/passport-login/web/qrcode/generate");
+        then.status(200).json_body(json!({
+            "code": 0,
+            "data": {"url": "https://account.bilibili.com/h5/account-h5/auth/scan-web?qrcode_key=k1", "qrcode_key": "k1"}
+        }));
+    });
+    let poll = |code: i32| {
+        server.mock(move |when, then| {
+            when.method(GET)
+                .path("/x/passport-login/web/qrcode/poll")
+                .query_param("qrcode_key", "k1");
+            then.status(200).json_body(json!({
+                "code": 0,
+                "data": {"url": "", "refresh_token": "", "timestamp": 0, "code": code, "message": ""}
+            }));
+        })
+    };
+
+    let login = QrLogin::new(login_options(&server))?;
+    let ticket = login.generate().await?;
+    assert_eq!(ticket.qrcode_key, "k1");
+    assert!(!render_qr(&ticket.url)?.is_empty());
+
+    let mut waiting = poll(86101);
+    assert_eq!(login.poll(&ticket).await?, QrPollStatus::Waiting);
+    waiting.delete();
+    let mut scanned = poll(86090);
+    assert_eq!(login.poll(&ticket).await?, QrPollStatus::Scanned);
+    scanned.delete();
+    let mut expired = poll(86038);
+    let err = login.wait_for_login(&ticket, |_| {}).await.unwrap_err();
+    assert!(matches!(err, FavlistError::Login(_)), "{err}");
+    expired.delete();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/x/passport-login/web/qrcode/poll");
+        then.status(200)
+            .header("set-cookie", "sid=abc; Path=/; Domain=bilibili.com")
+            .json_body(json!({
+                "code": 0,
+                "data": {
+                    "url": "https://passport.biligame.com/crossDomain?DedeUserID=42&DedeUserID__ckMd5=md5&Expires=1900000000&SESSDATA=sess%2Cdata&bili_jct=csrf&gourl=https%3A%2F%2Fwww.bilibili.com",
+                    "refresh_token": "rt-1",
+                    "timestamp": 1700000000000i64,
+                    "code": 0,
+                    "message": ""
+                }
+            }));
+    });
+    let mut statuses = Vec::new();
+    let credentials = login
+        .wait_for_login(&ticket, |status| statuses.push(status.clone()))
+        .await?;
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(credentials.refresh_token, "rt-1");
+    assert_eq!(credentials.expires_at, Some(1900000000));
+    assert_eq!(credentials.csrf(), Some("csrf"));
+    assert_eq!(
+        credentials.cookie_header(),
+        "DedeUserID=42; DedeUserID__ckMd5=md5; SESSDATA=sess,data; bili_jct=csrf; sid=abc"
+    );
+
+    let dir = tempdir()?;
+    let path = dir.path().join("credentials.json");
+    credentials.save(&path)?;
+    assert_eq!(LoginCredentials::load(&path)?, credentials);
+    assert_eq!(load_cookie_file(&path)?, credentials.cookie_header());
+    Ok(())
+}
+
+#[tokio::test]
+async fn expiring_credentials_are_refreshed() -> TestResult<()> {
+    let server = MockServer::start();
+    let correspond_path = Regex::new(r"^/correspond/1/[0-9a-f]{256}$")?;
+    let correspond = server.mock(|when, then| {
+        when.method(GET)
+            .path_matches(correspond_path)
+            .header("cookie", "SESSDATA=old; bili_jct=old-csrf");
+        then.status(200)
+            .body(r#"<html><div id="1-name">refresh-csrf-token</div></html>"#);
+    });
+    let refresh = server.mock(|when, then| {
+        when.method(POST)
+            .path("/x/passport-login/web/cookie/refresh")
+            .body_contains("refresh_csrf=refresh-csrf-token")
+            .body_contains("refresh_token=rt-old");
+        then.status(200)
+            .header(
+                "set-cookie",
+                "SESSDATA=new; Path=/; Domain=bilibili.com; Expires=Tue, 01 Jan 2030 00:00:00 GMT; HttpOnly",
+            )
+            .header("set-cookie", "bili_jct=new-csrf; Path=/; Domain=bilibili.com")
+            .json_body(json!({
+                "code": 0,
+                "data": {"status": 0, "message": "", "refresh_token": "rt-new"}
+            }));
+    });
+    let confirm = server.mock(|when, then| {
+        when.method(POST)
+            .path("/x/passport-login/web/confirm/refresh")
+            .header("cookie", "SESSDATA=new; bili_jct=new-csrf")
+            .body_contains("csrf=new-csrf")
+            .body_contains("refresh_token=rt-old");
+        then.status(200).json_body(json!({"code": 0, "data": null}));
+    });
+    let info = server.mock(|when, then| {
+        when.method(GET).path("/x/passport-login/web/cookie/info");
+        then.status(200)
+            .json_body(json!({"code": 0, "data": {"refresh": false, "timestamp": 0}}));
+    });
+
+    let dir = tempdir()?;
+    let path = dir.path().join("credentials.json");
+    let mut credentials = LoginCredentials {
+        refresh_token: "rt-old".to_string(),
+        expires_at: Some(unix_now() + 3600),
+        ..Default::default()
+    };
+    credentials
+        .cookies
+        .insert("SESSDATA".to_string(), "old".to_string());
+    credentials
+        .cookies
+        .insert("bili_jct".to_string(), "old-csrf".to_string());
+    credentials.save(&path)?;
+
+    let options = login_options(&server);
+    let refreshed = tokio::task::spawn_blocking(move || {
+        refresh_credentials_file_blocking(&path, options).map(|changed| (changed, path))
+    })
+    .await??;
+    assert!(refreshed.0);
+    let saved = LoginCredentials::load(&refreshed.1)?;
+    assert_eq!(saved.refresh_token, "rt-new");
+    assert_eq!(saved.cookies["SESSDATA"], "new");
+    assert_eq!(saved.expires_at, Some(1893456000));
+    correspond.assert();
+    refresh.assert();
+    confirm.assert();
+    info.assert_hits(0);
+
+    // 距离过期尚远且服务端不要求刷新时保持不变。
+    let login = QrLogin::new(login_options(&server))?;
+    assert_eq!(login.refresh_if_needed(&saved).await?, None);
+    info.assert_hits(1);
+    Ok(())
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}